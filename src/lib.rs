@@ -49,6 +49,22 @@ pub mod duration {
         }
     }
 
+    /// Formats a duration to a human readable text with hundredths of a second.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use librunner::duration;
+    ///
+    /// let d = Duration::from_millis(62_347);
+    /// assert_eq!(duration::format_duration_hundredths(d), "01:02.34");
+    /// ```
+    pub fn format_duration_hundredths(duration: Duration) -> String {
+        let hundredths = duration.subsec_millis() / 10;
+        format!("{}.{:02}", format_duration(Duration::new(duration.as_secs(), 0)), hundredths)
+    }
+
     #[cfg(test)]
     mod tests {
         use crate::duration;
@@ -72,6 +88,24 @@ pub mod duration {
 
 /// Functions to convert, format and do other things with distances.
 pub mod distance {
+    /// Meters in one kilometer.
+    pub const METERS_IN_KM: f32 = 1000.0;
+
+    /// Meters in one mile.
+    pub const METERS_IN_MILE: f32 = 1609.344;
+
+    /// Meters in one yard.
+    pub const METERS_IN_YARD: f32 = 0.9144;
+
+    /// Meters in one inch.
+    pub const METERS_IN_INCH: f32 = 0.0254;
+
+    /// Kilograms in one pound, the unit of weight used with the imperial scale.
+    pub const KG_PER_LB: f32 = 0.45359237;
+
+    /// Pounds in one kilogram.
+    pub const LBS_PER_KG: f32 = 1.0 / KG_PER_LB;
+
     /// Converts metters per second (m/s) to kilometers per hour (km/h).
     /// It is useful for converting raw values to readable ones.
     /// 
@@ -176,6 +210,28 @@ pub mod distance {
         f / 3.28084
     }
 
+    /// Formats a distance in splits, such as kilometers or miles, with one decimal,
+    /// two decimals when one would round it to a whole number, or without decimals
+    /// when it is a whole number.
+    ///
+    /// Example:
+    /// ```
+    /// use librunner::distance;
+    ///
+    /// assert_eq!(distance::format_distance(5.0), "5");
+    /// assert_eq!(distance::format_distance(42.195), "42.2");
+    /// assert_eq!(distance::format_distance(10.96), "10.96");
+    /// ```
+    pub fn format_distance(distance: f32) -> String {
+        if (distance - distance.round()).abs() < 0.005 {
+            format!("{:.0}", distance)
+        } else if (distance * 10.0).round() % 10.0 == 0.0 {
+            format!("{:.2}", distance)
+        } else {
+            format!("{:.1}", distance)
+        }
+    }
+
     #[cfg(test)]
     mod tests {
         use crate::distance;
@@ -185,18 +241,32 @@ pub mod distance {
             assert_eq!(distance::to_km_h(2.80), 10.08);
             assert_eq!(distance::to_km_h(10.0), 36.0);
         }
+
+        #[test]
+        fn test_format_distance_close_to_whole_numbers() {
+            assert_eq!(distance::format_distance(5.04), "5.04");
+            assert_eq!(distance::format_distance(10.96), "10.96");
+            assert_eq!(distance::format_distance(9.999), "10");
+            assert_eq!(distance::format_distance(21.0975), "21.1");
+        }
     }
 }
 
 /// API to make running calculations.
 pub mod running {
     use std::time::Duration;
+    use crate::distance;
+    use crate::heart_rate::HeartRate;
 
     /// A running race, already with common calculations that work with multiple scales.
     pub trait Race {
         /// The distance of one split in an implemented scale.
         const SPLIT_DISTANCE: u64;
 
+        /// The length of one distance unit of the implemented scale in meters.
+        /// It defaults to scales measured in meters.
+        const UNIT_IN_METERS: f32 = 1.0;
+
         /// Creates a new race with the basic attributes.
         /// 
         /// Example:
@@ -257,6 +327,31 @@ pub mod running {
         /// assert_eq!(m_race.num_splits(), 43);
         /// ```
         fn num_splits(&self) -> u64;
+
+        /// Returns the distance of each split. The last split is shorter than the others when the race
+        /// distance is not a multiple of the split distance.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use librunner::running::Race;
+        /// use librunner::running::MetricRace;
+        ///
+        /// let m_race: MetricRace = Race::new(2500);
+        /// assert_eq!(m_race.split_distances(), vec![1000, 1000, 500]);
+        /// ```
+        fn split_distances(&self) -> Vec<u64> {
+            let mut distances = Vec::new();
+            let mut remaining = self.distance();
+
+            while remaining > 0 {
+                let distance = remaining.min(Self::SPLIT_DISTANCE);
+                distances.push(distance);
+                remaining -= distance;
+            }
+
+            distances
+        }
     }
 
     /// A running race using the imperial scale, such as miles and yards.
@@ -266,6 +361,7 @@ pub mod running {
 
     impl Race for ImperialRace {
         const SPLIT_DISTANCE: u64 = 1760; // yards
+        const UNIT_IN_METERS: f32 = distance::METERS_IN_YARD;
 
         fn new(distance: u64) -> Self {
             ImperialRace {
@@ -295,6 +391,7 @@ pub mod running {
 
     impl Race for MetricRace {
         const SPLIT_DISTANCE: u64 = 1000; // meters
+        const UNIT_IN_METERS: f32 = 1.0;
 
         fn new(distance: u64) -> Self {
             MetricRace {
@@ -349,12 +446,79 @@ pub mod running {
         /// assert_eq!(i_runner.bmi() as u64, m_runner.bmi() as u64);
         /// ```
         fn bmi(&self) -> f32;
+
+        /// Returns the age of the runner in years.
+        fn age(&self) -> u64;
+
+        /// Returns the heart rate data known about the runner.
+        fn heart_rate(&self) -> &HeartRate;
+
+        /// Returns the weight of the runner in kilograms.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use librunner::running::Runner;
+        /// use librunner::running::ImperialRunner;
+        ///
+        /// let i_runner: ImperialRunner = Runner::new(187.425, 70.47, 44);
+        /// assert_eq!(i_runner.weight_in_kg().round(), 85.0);
+        /// ```
+        fn weight_in_kg(&self) -> f32;
+
+        /// Returns the height of the runner in meters.
+        fn height_in_m(&self) -> f32;
+
+        /// Returns the sex of the runner, if known.
+        fn sex(&self) -> Option<Sex>;
+
+        /// Returns how active the runner is during the day, besides running.
+        fn activity_level(&self) -> ActivityLevel;
+
+        /// Returns the body fat of the runner in percent of the weight, if known.
+        fn body_fat(&self) -> Option<f32>;
+    }
+
+    /// The biological sex of a runner, used by formulas that differ between men and women.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum Sex {
+        Male,
+        Female,
+    }
+
+    /// How active a person is during the day besides exercise, used to estimate the daily energy needs.
+    /// Runnings are counted separately, so they don't raise the activity level.
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    pub enum ActivityLevel {
+        #[default]
+        Sedentary,  // sitting most of the day, such as a desk job
+        Light,      // standing part of the day, such as a teacher
+        Moderate,   // walking most of the day, such as a waiter
+        Active,     // physical job, such as construction
+        VeryActive, // heavy physical job, such as farming
+    }
+
+    impl ActivityLevel {
+        /// Returns the factor to multiply the basal metabolic rate by to get the daily energy needs.
+        pub fn factor(&self) -> f32 {
+            match self {
+                ActivityLevel::Sedentary => 1.2,
+                ActivityLevel::Light => 1.375,
+                ActivityLevel::Moderate => 1.55,
+                ActivityLevel::Active => 1.725,
+                ActivityLevel::VeryActive => 1.9,
+            }
+        }
     }
 
     pub struct MetricRunner {
         pub weight: f32, // kilograms 
         pub height: f32, // meters
-        pub age:    u64  // years
+        pub age:    u64, // years
+        pub heart_rate: HeartRate,
+        pub sex: Option<Sex>,
+        pub activity_level: ActivityLevel,
+        pub body_fat: Option<f32>, // percent
     }
 
     impl Runner for MetricRunner {
@@ -362,19 +526,55 @@ pub mod running {
             MetricRunner { 
                 weight: weight, 
                 height: height, 
-                age: age 
+                age: age,
+                heart_rate: HeartRate::default(),
+                sex: None,
+                activity_level: ActivityLevel::default(),
+                body_fat: None,
             }
         }
 
         fn bmi(&self) -> f32 {
             self.weight / (self.height * self.height)
         }
+
+        fn age(&self) -> u64 {
+            self.age
+        }
+
+        fn heart_rate(&self) -> &HeartRate {
+            &self.heart_rate
+        }
+
+        fn weight_in_kg(&self) -> f32 {
+            self.weight
+        }
+
+        fn height_in_m(&self) -> f32 {
+            self.height
+        }
+
+        fn sex(&self) -> Option<Sex> {
+            self.sex
+        }
+
+        fn activity_level(&self) -> ActivityLevel {
+            self.activity_level
+        }
+
+        fn body_fat(&self) -> Option<f32> {
+            self.body_fat
+        }
     }
 
     pub struct ImperialRunner {
         pub weight: f32, // lbs
         pub height: f32, // in
-        pub age:    u64  // years
+        pub age:    u64, // years
+        pub heart_rate: HeartRate,
+        pub sex: Option<Sex>,
+        pub activity_level: ActivityLevel,
+        pub body_fat: Option<f32>, // percent
     }
 
     impl Runner for ImperialRunner {
@@ -382,13 +582,45 @@ pub mod running {
             ImperialRunner { 
                 weight: weight,
                 height: height,
-                age: age
+                age: age,
+                heart_rate: HeartRate::default(),
+                sex: None,
+                activity_level: ActivityLevel::default(),
+                body_fat: None,
             }
         }
         
         fn bmi(&self) -> f32 {
             self.weight / (self.height * self.height) * 703.0
         }
+
+        fn age(&self) -> u64 {
+            self.age
+        }
+
+        fn heart_rate(&self) -> &HeartRate {
+            &self.heart_rate
+        }
+
+        fn weight_in_kg(&self) -> f32 {
+            self.weight * distance::KG_PER_LB
+        }
+
+        fn height_in_m(&self) -> f32 {
+            self.height * distance::METERS_IN_INCH
+        }
+
+        fn sex(&self) -> Option<Sex> {
+            self.sex
+        }
+
+        fn activity_level(&self) -> ActivityLevel {
+            self.activity_level
+        }
+
+        fn body_fat(&self) -> Option<f32> {
+            self.body_fat
+        }
     }
 
     pub trait Running {
@@ -500,6 +732,8 @@ pub mod running {
         fn splits(&self, race: &impl Race) -> Vec<Duration>;
 
         /// Returns the splits of the race from a higher to a lower pace, according to the degree of variation.
+        /// The paces are rounded to the second, so see `pacing::PacingStrategy` for splits that add up to
+        /// the duration of the running.
         /// 
         /// # Arguments
         /// 
@@ -533,6 +767,8 @@ pub mod running {
         }
 
         /// Returns the splits of the race from a lower to a higher pace, according to the degree of variation.
+        /// The paces are rounded to the second, so see `pacing::PacingStrategy` for splits that add up to
+        /// the duration of the running.
         /// 
         /// # Arguments
         /// 
@@ -698,6 +934,5011 @@ pub mod running {
     }
 }
 
+/// API to estimate the effect of wind and drafting on the pace.
+pub mod wind {
+    use std::time::Duration;
+    use crate::running::Race;
+    use crate::running::Running;
+
+    /// Fraction of the energy cost spent against air resistance, per squared m/s of relative air speed.
+    /// Air resistance accounts for about 8% of the energy cost of running at 6 m/s in still air (Pugh, 1971).
+    pub const AIR_RESISTANCE_COST: f32 = 0.0022;
+
+    /// Fraction of the headwind cost that an equivalent tailwind gives back to the runner.
+    pub const TAILWIND_EFFICIENCY: f32 = 0.5;
+
+    /// Fraction of the air resistance eliminated when running about 1 m behind another runner.
+    pub const DRAFTING_EFFICIENCY: f32 = 0.8;
+
+    /// The wind blowing over the course.
+    pub struct Wind {
+        pub speed: f32,     // m/s
+        pub direction: f32, // degrees, where the wind comes from
+    }
+
+    impl Wind {
+        /// Creates a new wind with its speed in m/s and the direction it comes from in degrees.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use librunner::wind::Wind;
+        ///
+        /// let northerly = Wind::new(5.0, 0.0);
+        /// ```
+        pub fn new(speed: f32, direction: f32) -> Self {
+            Wind {
+                speed,
+                direction,
+            }
+        }
+
+        /// Calculates the component of the wind against a runner heading to the bearing, in m/s.
+        /// Negative values mean tailwind.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use librunner::wind::Wind;
+        ///
+        /// let northerly = Wind::new(5.0, 0.0);
+        /// assert_eq!(northerly.headwind(0.0), 5.0);
+        /// assert_eq!(northerly.headwind(180.0), -5.0);
+        /// ```
+        pub fn headwind(&self, bearing: f32) -> f32 {
+            self.speed * (self.direction - bearing).to_radians().cos()
+        }
+    }
+
+    /// A segment of the course, usually as long as a split.
+    pub struct Segment {
+        pub bearing: f32,  // degrees, where the runner is heading to
+        pub drafting: f32, // fraction of the segment run behind other runners, from 0.0 to 1.0
+    }
+
+    impl Segment {
+        /// Creates a new segment with the bearing of the course and the fraction of it run drafting.
+        pub fn new(bearing: f32, drafting: f32) -> Self {
+            Segment {
+                bearing,
+                drafting,
+            }
+        }
+    }
+
+    /// Calculates the factor to apply to the time of a segment run at `speed` (m/s) against a
+    /// `headwind` (m/s), spending a fraction `drafting` of the segment behind other runners.
+    /// It is 1.0 when running alone in still air, above 1.0 when the runner loses time and below 1.0
+    /// when the runner gains time.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::wind;
+    ///
+    /// assert_eq!(wind::adjustment_factor(4.0, 0.0, 0.0), 1.0);
+    /// assert!(wind::adjustment_factor(4.0, 4.0, 0.0) > 1.0);
+    /// assert!(wind::adjustment_factor(4.0, -4.0, 0.0) < 1.0);
+    /// assert!(wind::adjustment_factor(4.0, 0.0, 1.0) < 1.0);
+    /// ```
+    pub fn adjustment_factor(speed: f32, headwind: f32, drafting: f32) -> f32 {
+        let still_air = speed * speed;
+        let relative = speed + headwind;
+        let mut air = relative * relative.abs();
+
+        // the tailwind gives back only part of what the same headwind takes
+        if air < still_air {
+            air = still_air - (still_air - air) * TAILWIND_EFFICIENCY;
+        }
+
+        let shelter = 1.0 - drafting.clamp(0.0, 1.0) * DRAFTING_EFFICIENCY;
+        1.0 + AIR_RESISTANCE_COST * (air * shelter - still_air)
+    }
+
+    /// Adjusts the splits of the running to the wind, taking one segment of the course per split.
+    /// Splits without a corresponding segment are kept as they are.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use librunner::running::{Race, Running, MetricRace, MetricRunning};
+    /// use librunner::wind::{self, Wind, Segment};
+    ///
+    /// let race: MetricRace = Race::new(2000);
+    /// let running: MetricRunning = Running::new(Duration::new(500, 0));
+    /// let northerly = Wind::new(5.0, 0.0);
+    ///
+    /// // out to the north and back to the south
+    /// let segments = vec![Segment::new(0.0, 0.0), Segment::new(180.0, 0.0)];
+    /// let splits = wind::adjust_splits(&running, &race, &northerly, &segments);
+    ///
+    /// assert!(splits[0] > running.splits(&race)[0]);
+    /// assert!(splits[1] < running.splits(&race)[1]);
+    /// ```
+    pub fn adjust_splits<R: Race>(running: &impl Running, race: &R, wind: &Wind, segments: &[Segment]) -> Vec<Duration> {
+        let speed = running.speed(race) * R::UNIT_IN_METERS;
+        let mut adjusted = Vec::new();
+
+        for (i, split) in running.splits(race).iter().enumerate() {
+            match segments.get(i) {
+                Some(segment) => {
+                    let factor = adjustment_factor(speed, wind.headwind(segment.bearing), segment.drafting);
+                    adjusted.push(split.mul_f32(factor));
+                },
+                None => adjusted.push(*split),
+            }
+        }
+
+        adjusted
+    }
+
+    /// Calculates the time lost (positive) or gained (negative), in seconds, by the adjusted splits
+    /// compared to the splits in still air.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use librunner::wind;
+    ///
+    /// let still_air = vec![Duration::new(300, 0), Duration::new(300, 0)];
+    /// let adjusted = vec![Duration::new(310, 0), Duration::new(295, 0)];
+    /// assert_eq!(wind::time_difference(&still_air, &adjusted), 5.0);
+    /// ```
+    pub fn time_difference(still_air: &[Duration], adjusted: &[Duration]) -> f32 {
+        let still_air: f32 = still_air.iter().map(|split| split.as_secs_f32()).sum();
+        let adjusted: f32 = adjusted.iter().map(|split| split.as_secs_f32()).sum();
+        adjusted - still_air
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::time::Duration;
+        use crate::running::{Race, Running, MetricRace, MetricRunning, ImperialRace, ImperialRunning};
+        use crate::wind::{self, Wind, Segment};
+
+        #[test]
+        fn test_adjustment_factor() {
+            assert!((wind::adjustment_factor(4.0, 4.0, 0.0) - 1.1056).abs() < 0.0001);
+            assert!((wind::adjustment_factor(4.0, -2.0, 0.0) - 0.9868).abs() < 0.0001);
+            assert!((wind::adjustment_factor(4.0, 0.0, 1.0) - 0.97184).abs() < 0.0001);
+        }
+
+        #[test]
+        fn test_crosswind_has_no_effect() {
+            let westerly = Wind::new(5.0, 270.0);
+            assert!(westerly.headwind(0.0).abs() < 0.0001);
+        }
+
+        #[test]
+        fn test_adjust_imperial_splits() {
+            let race: ImperialRace = Race::new(3520);
+            let running: ImperialRunning = Running::new(Duration::new(960, 0));
+            let southerly = Wind::new(3.0, 180.0);
+            let segments = vec![Segment::new(180.0, 0.5)];
+
+            let still_air = running.splits(&race);
+            let adjusted = wind::adjust_splits(&running, &race, &southerly, &segments);
+
+            assert!(adjusted[0] > still_air[0]);
+            assert_eq!(adjusted[1], still_air[1]);
+            assert!(wind::time_difference(&still_air, &adjusted) > 0.0);
+        }
+
+        #[test]
+        fn test_drafting_in_still_air_saves_time() {
+            let race: MetricRace = Race::new(10000);
+            let running: MetricRunning = Running::new(Duration::new(2400, 0));
+            let calm = Wind::new(0.0, 0.0);
+            let segments: Vec<Segment> = (0..10).map(|_| Segment::new(90.0, 1.0)).collect();
+
+            let still_air = running.splits(&race);
+            let adjusted = wind::adjust_splits(&running, &race, &calm, &segments);
+
+            assert!(wind::time_difference(&still_air, &adjusted) < 0.0);
+        }
+    }
+}
+
+/// API to plan runnings on athletics tracks.
+pub mod track {
+    use std::f32::consts::PI;
+    use std::time::Duration;
+    use crate::duration;
+    use crate::running::Race;
+    use crate::running::Running;
+
+    /// An athletics track, where the lap is measured along lane 1.
+    pub enum Track {
+        Outdoor, // 400 m, 8 lanes
+        Indoor,  // 200 m, 6 lanes
+    }
+
+    impl Track {
+        /// Returns the distance of one lap in lane 1, in meters.
+        pub fn lap_distance(&self) -> f32 {
+            match self {
+                Track::Outdoor => 400.0,
+                Track::Indoor => 200.0,
+            }
+        }
+
+        /// Returns the width of each lane, in meters.
+        pub fn lane_width(&self) -> f32 {
+            match self {
+                Track::Outdoor => 1.22,
+                Track::Indoor => 1.0,
+            }
+        }
+
+        /// Returns the radius of the bends along the kerb, in meters.
+        pub fn bend_radius(&self) -> f32 {
+            match self {
+                Track::Outdoor => 36.5,
+                Track::Indoor => 17.5,
+            }
+        }
+
+        /// Returns the number of lanes of the track.
+        pub fn lanes(&self) -> u8 {
+            match self {
+                Track::Outdoor => 8,
+                Track::Indoor => 6,
+            }
+        }
+
+        /// Calculates the distance of one lap in the lane, in meters.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use librunner::track::Track;
+        ///
+        /// assert_eq!(Track::Outdoor.lane_distance(1), 400.0);
+        /// assert_eq!(Track::Outdoor.lane_distance(2).round(), 407.0);
+        /// ```
+        ///
+        /// # Panics
+        ///
+        /// Panics if the lane doesn't exist on the track.
+        pub fn lane_distance(&self, lane: u8) -> f32 {
+            self.lap_distance() + 2.0 * PI * self.lane_offset(lane)
+        }
+
+        /// Calculates how far ahead of lane 1 a runner starts in the lane to run the same distance,
+        /// in meters, when running the first `bends` bends in lanes. A 400 m race runs two bends in
+        /// lanes, while a 800 m race runs only one before the runners break for the inside lane.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use librunner::track::Track;
+        ///
+        /// let track = Track::Outdoor;
+        /// assert_eq!(track.stagger(1, 2), 0.0);
+        /// assert_eq!(track.stagger(2, 2).round(), 7.0);
+        /// assert_eq!(track.stagger(2, 1).round(), 4.0);
+        /// ```
+        ///
+        /// # Panics
+        ///
+        /// Panics if the lane doesn't exist on the track.
+        pub fn stagger(&self, lane: u8, bends: u8) -> f32 {
+            bends as f32 * PI * self.lane_offset(lane)
+        }
+
+        /// Calculates how far ahead of lane 1, along the bend, a runner starts on a waterfall start line
+        /// at the position of the lane, in meters. The curved line gives every runner the same distance
+        /// to the inside of the track at the end of the first bend.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use librunner::track::Track;
+        ///
+        /// let track = Track::Outdoor;
+        /// assert_eq!(track.waterfall(1), 0.0);
+        /// assert!(track.waterfall(2) < track.waterfall(3));
+        /// ```
+        ///
+        /// # Panics
+        ///
+        /// Panics if the lane doesn't exist on the track.
+        pub fn waterfall(&self, lane: u8) -> f32 {
+            self.check_lane(lane);
+            let inside = self.bend_radius() + 0.30;
+            let radius = inside + (lane - 1) as f32 * self.lane_width();
+
+            // a straight line tangent to the inside of the bend, then the rest of the bend
+            (radius * radius - inside * inside).sqrt() - inside * (inside / radius).acos()
+        }
+
+        /// Returns how far from the lane 1 measurement line the lane is measured, in meters.
+        /// Lane 1 is measured 0.30 m from the kerb and the other lanes 0.20 m from their inner line.
+        fn lane_offset(&self, lane: u8) -> f32 {
+            self.check_lane(lane);
+            if lane == 1 {
+                0.0
+            } else {
+                (lane - 1) as f32 * self.lane_width() - 0.10
+            }
+        }
+
+        fn check_lane(&self, lane: u8) {
+            assert!(lane >= 1 && lane <= self.lanes(), "lane {} doesn't exist on the track", lane);
+        }
+    }
+
+    /// The time when a lap is completed.
+    pub struct Lap {
+        pub number: u64,
+        pub distance: f32, // meters covered since the start, measured in lane 1
+        pub elapsed: Duration,
+    }
+
+    /// Calculates the laps of the running to complete the race on the track at even pace, running in the lane.
+    /// When the race distance is not a multiple of the lap, the first lap is the shorter one, like the first
+    /// 300 m of a 1500 m race.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::{Race, Running, MetricRace, MetricRunning};
+    /// use librunner::track::{self, Track};
+    /// use librunner::duration;
+    ///
+    /// let race: MetricRace = Race::new(1500);
+    /// let running: MetricRunning = Running::new(duration::to_duration(0, 3, 45));
+    /// let laps = track::lap_splits(&running, &race, &Track::Outdoor, 1);
+    ///
+    /// assert_eq!(laps.len(), 4);
+    /// assert_eq!(laps[0].distance, 300.0);
+    /// assert_eq!(laps[0].elapsed.as_secs(), 45);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lane doesn't exist on the track.
+    pub fn lap_splits<R: Race>(running: &impl Running, race: &R, track: &Track, lane: u8) -> Vec<Lap> {
+        let distance = race.distance() as f32 * R::UNIT_IN_METERS;
+        let lap_distance = track.lap_distance();
+        // a lap in an outer lane takes longer at the same pace
+        let pace = running.duration().as_secs_f64() / distance as f64
+            * (track.lane_distance(lane) / lap_distance) as f64;
+
+        let mut laps = Vec::new();
+        let mut covered = distance % lap_distance;
+        if covered < 0.5 {
+            covered += lap_distance;
+        }
+
+        while covered <= distance + 0.5 {
+            laps.push(Lap {
+                number: laps.len() as u64 + 1,
+                distance: covered,
+                elapsed: Duration::from_secs_f64(pace * covered as f64),
+            });
+            covered += lap_distance;
+        }
+
+        laps
+    }
+
+    /// Formats the laps as a schedule for pacers, one line per lap, such as "lap 1 at 00:45.00".
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::{Race, Running, MetricRace, MetricRunning};
+    /// use librunner::track::{self, Track};
+    /// use librunner::duration;
+    ///
+    /// let race: MetricRace = Race::new(800);
+    /// let running: MetricRunning = Running::new(duration::to_duration(0, 2, 10));
+    /// let laps = track::lap_splits(&running, &race, &Track::Outdoor, 1);
+    ///
+    /// assert_eq!(track::schedule(&laps), vec!["lap 1 at 01:05.00", "lap 2 at 02:10.00"]);
+    /// ```
+    pub fn schedule(laps: &[Lap]) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        for lap in laps {
+            lines.push(format!("lap {} at {}", lap.number, duration::format_duration_hundredths(lap.elapsed)));
+        }
+
+        lines
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::time::Duration;
+        use crate::running::{Race, Running, ImperialRace, ImperialRunning, MetricRace, MetricRunning};
+        use crate::track::{self, Track};
+
+        #[test]
+        fn test_lane_distance() {
+            assert!((Track::Outdoor.lane_distance(2) - 407.04).abs() < 0.01);
+            assert!((Track::Outdoor.lane_distance(8) - 453.03).abs() < 0.01);
+            assert!(Track::Indoor.lane_distance(2) > 200.0);
+        }
+
+        #[test]
+        #[should_panic]
+        fn test_missing_lane() {
+            Track::Indoor.lane_distance(7);
+        }
+
+        #[test]
+        fn test_stagger() {
+            let track = Track::Outdoor;
+            assert!((track.stagger(2, 1) * 2.0 - track.stagger(2, 2)).abs() < 0.001);
+            assert!(track.stagger(8, 2) > track.stagger(7, 2));
+        }
+
+        #[test]
+        fn test_lap_splits_in_outer_lane() {
+            let race: MetricRace = Race::new(400);
+            let running: MetricRunning = Running::new(Duration::new(80, 0));
+            let lane_1 = track::lap_splits(&running, &race, &Track::Outdoor, 1);
+            let lane_3 = track::lap_splits(&running, &race, &Track::Outdoor, 3);
+
+            assert_eq!(lane_1.len(), 1);
+            assert_eq!(lane_1[0].elapsed, Duration::new(80, 0));
+            assert!(lane_3[0].elapsed > lane_1[0].elapsed);
+        }
+
+        #[test]
+        fn test_imperial_mile_on_indoor_track() {
+            let race: ImperialRace = Race::new(1760);
+            let running: ImperialRunning = Running::new(Duration::new(240, 0));
+            let laps = track::lap_splits(&running, &race, &Track::Indoor, 1);
+
+            // 1609.34 m are 8.05 laps of 200 m: a partial lap of 9.34 m, then 8 full laps
+            assert_eq!(laps.len(), 9);
+            assert!((laps[0].distance - 9.344).abs() < 0.01);
+            assert!((laps[8].elapsed.as_secs_f32() - 240.0).abs() < 0.01);
+        }
+    }
+}
+
+/// API to distribute the duration of a running over the splits of a race.
+pub mod pacing {
+    use std::time::Duration;
+    use crate::running::Race;
+    use crate::running::Running;
+
+    /// The fastest pace a split can have relative to the average pace, as running at twice the
+    /// average speed is already far beyond any sensible pacing.
+    pub const MIN_RELATIVE_PACE: f32 = 0.5;
+
+    /// A way of pacing a race, defined by the pace of each split relative to the average pace.
+    pub trait PacingStrategy {
+        /// Returns the pace of each split of the race relative to the average pace,
+        /// where 1.0 is the average pace, and values above 1.0 are slower.
+        fn profile<R: Race>(&self, race: &R, duration: Duration) -> Vec<f32>;
+
+        /// Distributes the duration over the splits of the race according to the profile of the strategy.
+        /// The splits add up exactly to the duration, with sub-second precision, and the last split is
+        /// proportional to its distance when it is shorter than the others. Paces faster than
+        /// `MIN_RELATIVE_PACE` are clamped to it, and missing or non-finite paces are run at the average pace.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use std::time::Duration;
+        /// use librunner::running::{Race, MetricRace};
+        /// use librunner::pacing::{PacingStrategy, Even, Custom};
+        ///
+        /// let race: MetricRace = Race::new(2500);
+        /// let splits = Even.distribute(&race, Duration::new(600, 0));
+        /// assert_eq!(splits, vec![Duration::new(240, 0), Duration::new(240, 0), Duration::new(120, 0)]);
+        ///
+        /// // the last two splits are clamped to twice the average speed
+        /// let splits = Custom::new(vec![1.0, -0.5]).distribute(&race, Duration::new(600, 0));
+        /// assert_eq!(splits, vec![Duration::new(300, 0), Duration::new(200, 0), Duration::new(100, 0)]);
+        /// ```
+        fn distribute<R: Race>(&self, race: &R, duration: Duration) -> Vec<Duration> {
+            let profile = self.profile(race, duration);
+            let distances = race.split_distances();
+
+            let mut weights = Vec::new();
+            for (i, distance) in distances.iter().enumerate() {
+                let pace = match profile.get(i) {
+                    Some(pace) if pace.is_finite() => pace.max(MIN_RELATIVE_PACE),
+                    _ => 1.0,
+                };
+                weights.push(pace as f64 * *distance as f64);
+            }
+            let total_weight: f64 = weights.iter().sum();
+            let total = duration.as_nanos() as f64;
+
+            // rounding the cumulative time keeps the sum of the splits equal to the duration
+            let mut splits = Vec::new();
+            let mut weight = 0.0;
+            let mut elapsed = 0u64;
+            for w in weights {
+                weight += w;
+                let cumulative = (total * weight / total_weight).round() as u64;
+                splits.push(Duration::from_nanos(cumulative - elapsed));
+                elapsed = cumulative;
+            }
+
+            splits
+        }
+
+        /// Returns the splits of the race to complete it within the duration of the running.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use std::time::Duration;
+        /// use librunner::running::{Race, Running, MetricRace, MetricRunning};
+        /// use librunner::pacing::{PacingStrategy, Negative};
+        ///
+        /// let race: MetricRace = Race::new(42195);
+        /// let running: MetricRunning = Running::new(Duration::new(14400, 0));
+        /// let splits = Negative::new(Duration::new(10, 0)).splits(&running, &race);
+        ///
+        /// assert!(splits[0] > splits[41]);
+        /// assert_eq!(splits.iter().sum::<Duration>(), running.duration());
+        /// ```
+        fn splits<R: Race>(&self, running: &impl Running, race: &R) -> Vec<Duration> {
+            self.distribute(race, running.duration())
+        }
+    }
+
+    /// Calculates the average pace, in seconds per split distance.
+    fn average_pace<R: Race>(race: &R, duration: Duration) -> f32 {
+        duration.as_secs_f32() * R::SPLIT_DISTANCE as f32 / race.distance() as f32
+    }
+
+    /// Calculates the position of the split in the race, from 0.0 for the first to 1.0 for the last.
+    fn position(split: usize, num_splits: usize) -> f32 {
+        if num_splits > 1 {
+            split as f32 / (num_splits - 1) as f32
+        } else {
+            0.5
+        }
+    }
+
+    /// Calculates the time elapsed at a distance since the start, in the race scale, running the splits of the race.
+    /// The pace is constant within each split.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use librunner::running::{Race, MetricRace};
+    /// use librunner::pacing::{self, PacingStrategy, Even};
+    ///
+    /// let race: MetricRace = Race::new(2500);
+    /// let splits = Even.distribute(&race, Duration::new(750, 0));
+    ///
+    /// assert_eq!(pacing::elapsed_at(&race, &splits, 1500), Duration::new(450, 0));
+    /// assert_eq!(pacing::elapsed_at(&race, &splits, 5000), Duration::new(750, 0));
+    /// ```
+    pub fn elapsed_at<R: Race>(race: &R, splits: &[Duration], distance: u64) -> Duration {
+        let mut elapsed = Duration::ZERO;
+        let mut covered = 0;
+
+        for (i, split_distance) in race.split_distances().iter().enumerate() {
+            let split = match splits.get(i) {
+                Some(split) => *split,
+                None => break,
+            };
+
+            if covered + split_distance >= distance {
+                let fraction = (distance - covered) as f64 / *split_distance as f64;
+                return elapsed + split.mul_f64(fraction);
+            }
+            elapsed += split;
+            covered += split_distance;
+        }
+
+        elapsed
+    }
+
+    /// Runs all splits at the same pace.
+    pub struct Even;
+
+    impl PacingStrategy for Even {
+        fn profile<R: Race>(&self, race: &R, _duration: Duration) -> Vec<f32> {
+            vec![1.0; race.num_splits() as usize]
+        }
+    }
+
+    /// Starts `degree` slower than the average pace and finishes `degree` faster, changing the pace linearly.
+    /// The degree is limited so the last split is never faster than `MIN_RELATIVE_PACE`.
+    pub struct Negative {
+        pub degree: Duration,
+    }
+
+    impl Negative {
+        pub fn new(degree: Duration) -> Self {
+            Negative {
+                degree,
+            }
+        }
+    }
+
+    impl PacingStrategy for Negative {
+        fn profile<R: Race>(&self, race: &R, duration: Duration) -> Vec<f32> {
+            let variation = (self.degree.as_secs_f32() / average_pace(race, duration)).min(1.0 - MIN_RELATIVE_PACE);
+            let num_splits = race.num_splits() as usize;
+
+            let mut profile = Vec::new();
+            for split in 0..num_splits {
+                profile.push(1.0 + variation * (1.0 - 2.0 * position(split, num_splits)));
+            }
+
+            profile
+        }
+    }
+
+    /// Starts `degree` faster than the average pace and finishes `degree` slower, changing the pace linearly.
+    /// The degree is limited so the first split is never faster than `MIN_RELATIVE_PACE`.
+    pub struct Positive {
+        pub degree: Duration,
+    }
+
+    impl Positive {
+        pub fn new(degree: Duration) -> Self {
+            Positive {
+                degree,
+            }
+        }
+    }
+
+    impl PacingStrategy for Positive {
+        fn profile<R: Race>(&self, race: &R, duration: Duration) -> Vec<f32> {
+            let variation = (self.degree.as_secs_f32() / average_pace(race, duration)).min(1.0 - MIN_RELATIVE_PACE);
+            let num_splits = race.num_splits() as usize;
+
+            let mut profile = Vec::new();
+            for split in 0..num_splits {
+                profile.push(1.0 - variation * (1.0 - 2.0 * position(split, num_splits)));
+            }
+
+            profile
+        }
+    }
+
+    /// Holds the same pace for the first two thirds of the race, then speeds up linearly
+    /// until the last split is `degree` faster than the opening pace, but never faster than `MIN_RELATIVE_PACE`.
+    pub struct ProgressiveLastThird {
+        pub degree: Duration,
+    }
+
+    impl ProgressiveLastThird {
+        pub fn new(degree: Duration) -> Self {
+            ProgressiveLastThird {
+                degree,
+            }
+        }
+    }
+
+    impl PacingStrategy for ProgressiveLastThird {
+        fn profile<R: Race>(&self, race: &R, duration: Duration) -> Vec<f32> {
+            let variation = (self.degree.as_secs_f32() / average_pace(race, duration)).min(1.0 - MIN_RELATIVE_PACE);
+            let num_splits = race.num_splits() as usize;
+            let last_third = num_splits * 2 / 3;
+
+            let mut profile = Vec::new();
+            for split in 0..num_splits {
+                if split < last_third {
+                    profile.push(1.0);
+                } else {
+                    let progress = (split - last_third + 1) as f32 / (num_splits - last_third) as f32;
+                    profile.push(1.0 - variation * progress);
+                }
+            }
+
+            profile
+        }
+    }
+
+    /// Follows a custom curve of relative paces along the race, where 1.0 is the average pace and
+    /// values above 1.0 are slower. The curve is stretched over the race, so it doesn't need
+    /// one point per split.
+    pub struct Custom {
+        pub curve: Vec<f32>,
+    }
+
+    impl Custom {
+        /// Creates a custom strategy from a curve of relative paces.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use std::time::Duration;
+        /// use librunner::running::{Race, MetricRace};
+        /// use librunner::pacing::{PacingStrategy, Custom};
+        ///
+        /// // slow start, fast middle, slow finish
+        /// let curve = Custom::new(vec![1.05, 0.95, 1.05]);
+        /// let race: MetricRace = Race::new(10000);
+        /// let splits = curve.distribute(&race, Duration::new(3000, 0));
+        ///
+        /// assert!(splits[0] > splits[5]);
+        /// assert!(splits[9] > splits[5]);
+        /// ```
+        pub fn new(curve: Vec<f32>) -> Self {
+            Custom {
+                curve,
+            }
+        }
+
+        /// Samples the curve at the position, from 0.0 at the start to 1.0 at the finish.
+        fn sample(&self, position: f32) -> f32 {
+            match self.curve.len() {
+                0 => 1.0,
+                1 => self.curve[0],
+                len => {
+                    let point = position * (len - 1) as f32;
+                    let before = (point.floor() as usize).min(len - 2);
+                    let fraction = point - before as f32;
+                    self.curve[before] + (self.curve[before + 1] - self.curve[before]) * fraction
+                }
+            }
+        }
+    }
+
+    impl PacingStrategy for Custom {
+        fn profile<R: Race>(&self, race: &R, _duration: Duration) -> Vec<f32> {
+            let num_splits = race.num_splits() as usize;
+
+            let mut profile = Vec::new();
+            for split in 0..num_splits {
+                // the middle of the split
+                let position = (split as f32 + 0.5) / num_splits as f32;
+                profile.push(self.sample(position));
+            }
+
+            profile
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::time::Duration;
+        use crate::running::{Race, Running, ImperialRace, ImperialRunning, MetricRace, MetricRunning};
+        use crate::pacing::{PacingStrategy, Even, Negative, Positive, ProgressiveLastThird, Custom};
+
+        fn secs(splits: &[Duration]) -> Vec<u64> {
+            splits.iter().map(|split| split.as_secs_f64().round() as u64).collect()
+        }
+
+        #[test]
+        fn test_splits_add_up_to_duration() {
+            let race: MetricRace = Race::new(42195);
+            let running: MetricRunning = Running::new(Duration::new(10799, 0));
+            let degree = Duration::new(7, 0);
+
+            assert_eq!(Even.splits(&running, &race).iter().sum::<Duration>(), running.duration());
+            assert_eq!(Negative::new(degree).splits(&running, &race).iter().sum::<Duration>(), running.duration());
+            assert_eq!(Positive::new(degree).splits(&running, &race).iter().sum::<Duration>(), running.duration());
+            assert_eq!(ProgressiveLastThird::new(degree).splits(&running, &race).iter().sum::<Duration>(), running.duration());
+            assert_eq!(Custom::new(vec![1.1, 0.9]).splits(&running, &race).iter().sum::<Duration>(), running.duration());
+        }
+
+        #[test]
+        fn test_extreme_degrees_keep_their_direction() {
+            let race: MetricRace = Race::new(10000);
+            let running: MetricRunning = Running::new(Duration::new(3000, 0));
+
+            let negative = Negative::new(Duration::new(400, 0)).splits(&running, &race);
+            assert!(negative[0] > negative[9]);
+            assert_eq!(negative.iter().sum::<Duration>(), running.duration());
+            assert_eq!(secs(&negative), vec![450, 417, 383, 350, 317, 283, 250, 217, 183, 150]);
+
+            let positive = Positive::new(Duration::new(400, 0)).splits(&running, &race);
+            assert!(positive[0] < positive[9]);
+            assert_eq!(positive.iter().sum::<Duration>(), running.duration());
+        }
+
+        #[test]
+        fn test_paces_that_cant_be_run_are_clamped() {
+            let race: MetricRace = Race::new(10000);
+            let running: MetricRunning = Running::new(Duration::new(3000, 0));
+            let even = Even.splits(&running, &race);
+
+            let splits = Custom::new(vec![1.0, -0.5]).splits(&running, &race);
+            assert!(splits[0] > splits[9]);
+            assert_eq!(secs(&splits)[8], secs(&splits)[9]);
+            assert_eq!(splits.iter().sum::<Duration>(), running.duration());
+
+            assert_eq!(Custom::new(vec![0.0]).splits(&running, &race), even);
+            assert_eq!(Custom::new(vec![f32::NAN]).splits(&running, &race), even);
+        }
+
+        #[test]
+        fn test_negative_splits_with_few_splits() {
+            let race: MetricRace = Race::new(3000);
+            let running: MetricRunning = Running::new(Duration::new(900, 0));
+            let splits = Negative::new(Duration::new(5, 0)).splits(&running, &race);
+
+            assert_eq!(secs(&splits), vec![305, 300, 295]);
+        }
+
+        #[test]
+        fn test_positive_splits() {
+            let race: ImperialRace = Race::new(5280);
+            let running: ImperialRunning = Running::new(Duration::new(1800, 0));
+            let splits = Positive::new(Duration::new(10, 0)).splits(&running, &race);
+
+            assert_eq!(secs(&splits), vec![590, 600, 610]);
+        }
+
+        #[test]
+        fn test_progressive_last_third() {
+            let race: MetricRace = Race::new(9000);
+            let running: MetricRunning = Running::new(Duration::new(2700, 0));
+            let splits = ProgressiveLastThird::new(Duration::new(9, 0)).splits(&running, &race);
+
+            assert_eq!(splits[0], splits[5]);
+            assert!(splits[6] < splits[5]);
+            assert!(splits[8] < splits[7]);
+        }
+
+        #[test]
+        fn test_single_split() {
+            let race: MetricRace = Race::new(800);
+            let running: MetricRunning = Running::new(Duration::new(150, 0));
+            assert_eq!(Negative::new(Duration::new(5, 0)).splits(&running, &race), vec![Duration::new(150, 0)]);
+        }
+    }
+}
+
+/// API to generate printable pace bands for race day.
+pub mod pace_band {
+    use std::time::Duration;
+    use crate::distance;
+    use crate::duration;
+    use crate::pacing::PacingStrategy;
+    use crate::running::Race;
+    use crate::running::Running;
+
+    /// A marker of the pace band, at the end of each split.
+    pub struct Marker {
+        pub distance: f32, // split units since the start, such as 5.0 km
+        pub meters: f32,   // meters since the start
+        pub split: Duration,
+        pub elapsed: Duration,
+    }
+
+    /// Options to lay out a pace band.
+    #[derive(Default)]
+    pub struct Options {
+        pub both_units: bool, // adds a column with the distance in the other unit
+        pub compact: bool,    // only the distance and the elapsed time, for narrow bands
+    }
+
+    /// A pace band with the time to reach every split marker of a race.
+    pub struct PaceBand {
+        pub unit: &'static str,
+        pub other_unit: &'static str,
+        pub other_unit_in_meters: f32,
+        pub goal: Duration,
+        pub markers: Vec<Marker>,
+    }
+
+    impl PaceBand {
+        /// Creates a pace band for the running to complete the race following the pacing strategy.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use std::time::Duration;
+        /// use librunner::running::{Race, Running, MetricRace, MetricRunning};
+        /// use librunner::pacing::Even;
+        /// use librunner::pace_band::{PaceBand, Options};
+        ///
+        /// let race: MetricRace = Race::new(42195);
+        /// let running: MetricRunning = Running::new(Duration::new(14400, 0));
+        /// let band = PaceBand::new(&race, &running, &Even);
+        ///
+        /// assert_eq!(band.markers.len(), 43);
+        /// print!("{}", band.to_text(&Options::default()));
+        /// ```
+        pub fn new<R: Race>(race: &R, running: &impl Running, strategy: &impl PacingStrategy) -> Self {
+            let splits = strategy.splits(running, race);
+            let distances = race.split_distances();
+
+            let mut markers = Vec::new();
+            let mut covered = 0;
+            let mut elapsed = Duration::ZERO;
+            for (i, split) in splits.iter().enumerate() {
+                covered += distances[i];
+                elapsed += *split;
+                markers.push(Marker {
+                    distance: covered as f32 / R::SPLIT_DISTANCE as f32,
+                    meters: covered as f32 * R::UNIT_IN_METERS,
+                    split: *split,
+                    elapsed,
+                });
+            }
+
+            PaceBand {
+                unit: split_unit::<R>(),
+                other_unit: other_unit::<R>().0,
+                other_unit_in_meters: other_unit::<R>().1,
+                goal: running.duration(),
+                markers,
+            }
+        }
+
+        /// Returns the columns of the band: the header and one row per marker.
+        fn table(&self, options: &Options) -> Vec<Vec<String>> {
+            let mut header = vec![self.unit.to_string()];
+            if options.both_units {
+                header.push(self.other_unit.to_string());
+            }
+            if !options.compact {
+                header.push("split".to_string());
+            }
+            header.push("time".to_string());
+
+            let mut rows = vec![header];
+            for marker in &self.markers {
+                let mut row = vec![distance::format_distance(marker.distance)];
+                if options.both_units {
+                    row.push(format!("{:.2}", marker.meters / self.other_unit_in_meters));
+                }
+                if !options.compact {
+                    row.push(format_time(marker.split));
+                }
+                row.push(format_time(marker.elapsed));
+                rows.push(row);
+            }
+
+            rows
+        }
+
+        /// Returns the title of the band, such as "42.2 km in 04:00:00".
+        fn title(&self) -> String {
+            let distance = match self.markers.last() {
+                Some(marker) => marker.distance,
+                None => 0.0,
+            };
+            format!("{} {} in {}", distance::format_distance(distance), self.unit, format_time(self.goal))
+        }
+
+        /// Formats the pace band as plain text, with aligned columns.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use std::time::Duration;
+        /// use librunner::running::{Race, Running, MetricRace, MetricRunning};
+        /// use librunner::pacing::Even;
+        /// use librunner::pace_band::{PaceBand, Options};
+        ///
+        /// let race: MetricRace = Race::new(2000);
+        /// let running: MetricRunning = Running::new(Duration::new(600, 0));
+        /// let band = PaceBand::new(&race, &running, &Even);
+        ///
+        /// assert_eq!(band.to_text(&Options::default()),
+        ///            "2 km in 10:00\nkm  split  time\n1   05:00  05:00\n2   05:00  10:00\n");
+        /// assert_eq!(band.to_text(&Options { both_units: false, compact: true }),
+        ///            "2 km in 10:00\nkm time\n1  05:00\n2  10:00\n");
+        /// ```
+        pub fn to_text(&self, options: &Options) -> String {
+            let table = self.table(options);
+            let separator = if options.compact { 1 } else { 2 };
+
+            let mut widths = vec![0; table[0].len()];
+            for row in &table {
+                for (i, cell) in row.iter().enumerate() {
+                    widths[i] = widths[i].max(cell.len());
+                }
+            }
+
+            let mut text = format!("{}\n", self.title());
+            for row in &table {
+                let mut line = String::new();
+                for (i, cell) in row.iter().enumerate() {
+                    if i < row.len() - 1 {
+                        line.push_str(&format!("{:width$}", cell, width = widths[i] + separator));
+                    } else {
+                        line.push_str(cell);
+                    }
+                }
+                text.push_str(&line);
+                text.push('\n');
+            }
+
+            text
+        }
+
+        /// Formats the pace band as an HTML table, ready to be styled and printed.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use std::time::Duration;
+        /// use librunner::running::{Race, Running, ImperialRace, ImperialRunning};
+        /// use librunner::pacing::Even;
+        /// use librunner::pace_band::{PaceBand, Options};
+        ///
+        /// let race: ImperialRace = Race::new(5280);
+        /// let running: ImperialRunning = Running::new(Duration::new(1800, 0));
+        /// let band = PaceBand::new(&race, &running, &Even);
+        /// let html = band.to_html(&Options { both_units: true, compact: false });
+        ///
+        /// assert!(html.starts_with("<table class=\"pace-band\">"));
+        /// assert!(html.contains("<td>3</td><td>4.83</td><td>10:00</td><td>30:00</td>"));
+        /// ```
+        pub fn to_html(&self, options: &Options) -> String {
+            let table = self.table(options);
+            let class = if options.compact { "pace-band compact" } else { "pace-band" };
+
+            let mut html = format!("<table class=\"{}\">\n<caption>{}</caption>\n<thead>\n<tr>", class, self.title());
+            for cell in &table[0] {
+                html.push_str(&format!("<th>{}</th>", cell));
+            }
+            html.push_str("</tr>\n</thead>\n<tbody>\n");
+
+            for row in &table[1..] {
+                html.push_str("<tr>");
+                for cell in row {
+                    html.push_str(&format!("<td>{}</td>", cell));
+                }
+                html.push_str("</tr>\n");
+            }
+            html.push_str("</tbody>\n</table>\n");
+
+            html
+        }
+
+        /// Formats the pace band as an SVG image, one row per marker.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use std::time::Duration;
+        /// use librunner::running::{Race, Running, MetricRace, MetricRunning};
+        /// use librunner::pacing::Even;
+        /// use librunner::pace_band::{PaceBand, Options};
+        ///
+        /// let race: MetricRace = Race::new(5000);
+        /// let running: MetricRunning = Running::new(Duration::new(1500, 0));
+        /// let band = PaceBand::new(&race, &running, &Even);
+        /// let svg = band.to_svg(&Options { both_units: false, compact: true });
+        ///
+        /// assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"120\" height=\"140\""));
+        /// ```
+        pub fn to_svg(&self, options: &Options) -> String {
+            let table = self.table(options);
+            let column_width = if options.compact { 60 } else { 80 };
+            let row_height = 20;
+            let width = column_width * table[0].len();
+            // the title and the header take one row each
+            let height = row_height * (table.len() + 1);
+
+            let mut svg = format!(
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"monospace\" font-size=\"12\">\n",
+                width, height);
+            svg.push_str(&format!("<rect width=\"{}\" height=\"{}\" fill=\"white\" stroke=\"black\"/>\n", width, height));
+            svg.push_str(&format!("<text x=\"4\" y=\"15\" font-weight=\"bold\">{}</text>\n", self.title()));
+
+            for (r, row) in table.iter().enumerate() {
+                let y = row_height * (r + 2) - 5;
+                for (c, cell) in row.iter().enumerate() {
+                    let weight = if r == 0 { " font-weight=\"bold\"" } else { "" };
+                    svg.push_str(&format!("<text x=\"{}\" y=\"{}\"{}>{}</text>\n", column_width * c + 4, y, weight, cell));
+                }
+            }
+            svg.push_str("</svg>\n");
+
+            svg
+        }
+    }
+
+    /// Returns the abbreviation of the split distance of the race scale: "km" for races in meters and "mi" for
+    /// races in yards.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::{MetricRace, ImperialRace};
+    /// use librunner::pace_band;
+    ///
+    /// assert_eq!(pace_band::split_unit::<MetricRace>(), "km");
+    /// assert_eq!(pace_band::split_unit::<ImperialRace>(), "mi");
+    /// ```
+    pub fn split_unit<R: Race>() -> &'static str {
+        if R::UNIT_IN_METERS == 1.0 { "km" } else { "mi" }
+    }
+
+    /// Returns the abbreviation of the split distance in the other scale and its length in meters.
+    fn other_unit<R: Race>() -> (&'static str, f32) {
+        if R::UNIT_IN_METERS == 1.0 {
+            ("mi", distance::METERS_IN_MILE)
+        } else {
+            ("km", distance::METERS_IN_KM)
+        }
+    }
+
+    /// Formats the time rounded to the second.
+    fn format_time(time: Duration) -> String {
+        duration::format_duration(Duration::new(time.as_secs_f64().round() as u64, 0))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::time::Duration;
+        use crate::running::{Race, Running, MetricRace, MetricRunning};
+        use crate::pacing::Negative;
+        use crate::pace_band::{PaceBand, Options};
+
+        #[test]
+        fn test_marathon_band() {
+            let race: MetricRace = Race::new(42195);
+            let running: MetricRunning = Running::new(Duration::new(10800, 0));
+            let band = PaceBand::new(&race, &running, &Negative::new(Duration::new(6, 0)));
+            let text = band.to_text(&Options { both_units: true, compact: false });
+
+            assert!(text.starts_with("42.2 km in 03:00:00\nkm"));
+            assert!(text.trim_end().ends_with("03:00:00"));
+            assert_eq!(band.markers[41].distance, 42.0);
+            assert!(band.markers[0].split > band.markers[41].split);
+        }
+
+        #[test]
+        fn test_compact_html_has_no_split_column() {
+            let race: MetricRace = Race::new(3000);
+            let running: MetricRunning = Running::new(Duration::new(720, 0));
+            let band = PaceBand::new(&race, &running, &Negative::new(Duration::new(3, 0)));
+            let html = band.to_html(&Options { both_units: false, compact: true });
+
+            assert!(html.contains("<table class=\"pace-band compact\">"));
+            assert!(html.contains("<tr><th>km</th><th>time</th></tr>"));
+            assert!(html.contains("<tr><td>3</td><td>12:00</td></tr>"));
+        }
+    }
+}
+
+/// API to analyse the splits recorded in a race.
+pub mod analysis {
+    use std::time::Duration;
+    use crate::pacing;
+    use crate::running::Race;
+
+    /// Maximum difference between the halves of the race, in percent of the first half, for an even race.
+    pub const EVEN_TOLERANCE: f32 = 1.0;
+
+    /// Minimum fade in the last quarter, in percent, for a positive race to be considered blown up.
+    pub const BLOWN_UP_FADE: f32 = 10.0;
+
+    /// How the race was paced, according to the difference between its halves.
+    #[derive(Debug, PartialEq)]
+    pub enum SplitType {
+        Even,
+        Negative, // second half faster than the first
+        Positive, // second half slower than the first
+        BlownUp,  // second half slower, with a strong fade at the end
+    }
+
+    /// The analysis of the splits of a race, comparing their paces, so a shorter last split is not mistaken for a fast one.
+    pub struct Analysis {
+        pub fastest: usize, // index of the split with the fastest pace
+        pub slowest: usize, // index of the split with the slowest pace
+        pub coefficient_of_variation: f32, // standard deviation of the paces in percent of the average pace
+        pub half_differential: f32, // seconds of the second half minus seconds of the first half
+        pub fade: f32, // percent the last quarter was slower than the rest of the race
+        pub split_type: SplitType,
+    }
+
+    impl Analysis {
+        /// Analyses the splits of the race, one for each split distance of the race.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use librunner::running::{Race, MetricRace};
+        /// use librunner::analysis::{Analysis, SplitType};
+        /// use librunner::duration;
+        ///
+        /// let race: MetricRace = Race::new(5000);
+        /// let splits = vec![
+        ///     duration::to_duration(0, 5, 53),
+        ///     duration::to_duration(0, 5, 38),
+        ///     duration::to_duration(0, 5, 44),
+        ///     duration::to_duration(0, 5, 37),
+        ///     duration::to_duration(0, 5, 29),
+        /// ];
+        /// let analysis = Analysis::new(&race, &splits);
+        ///
+        /// assert_eq!(analysis.fastest, 4);
+        /// assert_eq!(analysis.slowest, 0);
+        /// assert_eq!(analysis.half_differential, -25.0);
+        /// assert_eq!(analysis.split_type, SplitType::Negative);
+        /// ```
+        ///
+        /// # Panics
+        ///
+        /// Panics if there are no splits or their number differs from the number of splits of the race.
+        pub fn new<R: Race>(race: &R, splits: &[Duration]) -> Self {
+            let distances = race.split_distances();
+            assert!(!splits.is_empty(), "there are no splits to analyse");
+            assert_eq!(splits.len(), distances.len(), "there must be one split for each split distance");
+
+            // seconds per split distance, weighted by the distance of the split
+            let paces: Vec<f32> = splits.iter()
+                .zip(&distances)
+                .map(|(split, distance)| split.as_secs_f32() * R::SPLIT_DISTANCE as f32 / *distance as f32)
+                .collect();
+            let weights: Vec<f32> = distances.iter().map(|distance| *distance as f32 / race.distance() as f32).collect();
+
+            let mut fastest = 0;
+            let mut slowest = 0;
+            for (i, pace) in paces.iter().enumerate() {
+                if *pace < paces[fastest] {
+                    fastest = i;
+                }
+                if *pace > paces[slowest] {
+                    slowest = i;
+                }
+            }
+
+            let mean: f32 = paces.iter().zip(&weights).map(|(pace, weight)| pace * weight).sum();
+            let variance: f32 = paces.iter().zip(&weights).map(|(pace, weight)| weight * (pace - mean).powi(2)).sum();
+            let coefficient_of_variation = variance.sqrt() / mean * 100.0;
+
+            // the halves and quarters are split by distance, within the split where they end
+            let total = splits.iter().sum::<Duration>().as_secs_f32();
+            let first_half = elapsed_at(race, splits, race.distance() as f32 / 2.0);
+            let half_differential = (total - first_half) - first_half;
+
+            let fade = if splits.len() > 1 {
+                let three_quarters = race.distance() as f32 * 0.75;
+                let before = elapsed_at(race, splits, three_quarters);
+                let last_quarter = (total - before) / (race.distance() as f32 - three_quarters);
+                (last_quarter / (before / three_quarters) - 1.0) * 100.0
+            } else {
+                0.0
+            };
+
+            let differential = half_differential / first_half * 100.0;
+            let split_type = if differential.abs() <= EVEN_TOLERANCE {
+                SplitType::Even
+            } else if differential < 0.0 {
+                SplitType::Negative
+            } else if fade >= BLOWN_UP_FADE {
+                SplitType::BlownUp
+            } else {
+                SplitType::Positive
+            };
+
+            Analysis {
+                fastest,
+                slowest,
+                coefficient_of_variation,
+                half_differential,
+                fade,
+                split_type,
+            }
+        }
+    }
+
+    /// Calculates the seconds elapsed at a distance that may fall between two units of the race scale.
+    fn elapsed_at<R: Race>(race: &R, splits: &[Duration], distance: f32) -> f32 {
+        let before = pacing::elapsed_at(race, splits, distance.floor() as u64).as_secs_f32();
+        let after = pacing::elapsed_at(race, splits, distance.ceil() as u64).as_secs_f32();
+        before + (after - before) * distance.fract()
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::time::Duration;
+        use crate::running::{Race, Running, MetricRace, MetricRunning};
+        use crate::pacing::{PacingStrategy, Even};
+        use crate::analysis::{Analysis, SplitType};
+
+        fn splits(secs: &[u64]) -> Vec<Duration> {
+            secs.iter().map(|s| Duration::new(*s, 0)).collect()
+        }
+
+        fn race(secs: &[u64]) -> MetricRace {
+            Race::new(secs.len() as u64 * 1000)
+        }
+
+        fn analyse(secs: &[u64]) -> Analysis {
+            Analysis::new(&race(secs), &splits(secs))
+        }
+
+        #[test]
+        fn test_even_race() {
+            let analysis = analyse(&[300, 300, 301, 299, 300, 300]);
+            assert_eq!(analysis.split_type, SplitType::Even);
+            assert!(analysis.coefficient_of_variation < 0.5);
+        }
+
+        #[test]
+        fn test_positive_race() {
+            let analysis = analyse(&[300, 300, 305, 310, 312, 315, 318, 320]);
+            assert_eq!(analysis.half_differential, 50.0);
+            assert_eq!(analysis.split_type, SplitType::Positive);
+            assert_eq!(analysis.slowest, 7);
+        }
+
+        #[test]
+        fn test_blown_up_race() {
+            let analysis = analyse(&[300, 300, 300, 300, 300, 300, 360, 380]);
+            assert!(analysis.fade > 20.0);
+            assert_eq!(analysis.split_type, SplitType::BlownUp);
+        }
+
+        #[test]
+        fn test_single_split() {
+            let analysis = analyse(&[300]);
+            assert_eq!(analysis.fade, 0.0);
+            assert_eq!(analysis.split_type, SplitType::Even);
+        }
+
+        #[test]
+        fn test_even_marathon_with_short_last_split() {
+            let race: MetricRace = Race::new(42195);
+            let running: MetricRunning = Running::new(Duration::new(14400, 0));
+            let analysis = Analysis::new(&race, &Even.splits(&running, &race));
+
+            assert_eq!(analysis.split_type, SplitType::Even);
+            assert!(analysis.half_differential.abs() < 0.1);
+            assert!(analysis.fade.abs() < 0.01);
+            assert!(analysis.coefficient_of_variation < 0.01);
+        }
+    }
+}
+
+/// API to compare the planned splits of a race with the actual ones.
+pub mod comparison {
+    use std::time::Duration;
+
+    /// Seconds behind the plan tolerated before the plan is considered lost.
+    pub const PLAN_TOLERANCE: f32 = 10.0;
+
+    /// Number of recent splits used to project the finish from the current trend.
+    pub const TREND_SPLITS: usize = 3;
+
+    /// The difference between a planned and an actual split.
+    pub struct SplitDelta {
+        pub planned: Duration,
+        pub actual: Duration,
+        pub delta: f32,      // seconds, positive when behind the plan and negative when ahead
+        pub cumulative: f32, // seconds since the start, positive when behind the plan and negative when ahead
+    }
+
+    impl SplitDelta {
+        /// Returns true if the runner is ahead of the plan at the end of the split.
+        pub fn is_ahead(&self) -> bool {
+            self.cumulative < 0.0
+        }
+    }
+
+    /// The comparison of a planned race with the actual splits.
+    pub struct Comparison {
+        pub splits: Vec<SplitDelta>,
+        pub plan_lost_at: Option<usize>, // index of the split after which the runner never got back on plan
+        pub projected_finish: Duration,
+    }
+
+    impl Comparison {
+        /// Compares the planned splits, such as the ones from `PacingStrategy::splits`, with the actual ones.
+        /// The planned splits must be the times of each split, adding up to the goal, so a shorter last split
+        /// has a shorter time. `Running::splits` doesn't fit, since its last split is a full split at the average pace.
+        /// The actual splits may be fewer than the planned ones while the race is in progress, and the finish is
+        /// then projected from the trend of the last splits.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use std::time::Duration;
+        /// use librunner::running::{Race, Running, MetricRace, MetricRunning};
+        /// use librunner::pacing::{PacingStrategy, Even};
+        /// use librunner::comparison::Comparison;
+        /// use librunner::duration;
+        ///
+        /// let race: MetricRace = Race::new(5000);
+        /// let running: MetricRunning = Running::new(duration::to_duration(0, 25, 0));
+        /// let actual = vec![
+        ///     duration::to_duration(0, 4, 55),
+        ///     duration::to_duration(0, 5, 2),
+        ///     duration::to_duration(0, 5, 10),
+        /// ];
+        /// let comparison = Comparison::new(&Even.splits(&running, &race), &actual);
+        ///
+        /// assert_eq!(comparison.splits[0].delta, -5.0);
+        /// assert!(comparison.splits[0].is_ahead());
+        /// assert_eq!(comparison.splits[2].cumulative, 7.0);
+        /// assert_eq!(comparison.plan_lost_at, None);
+        /// assert!(comparison.projected_finish > running.duration());
+        /// ```
+        pub fn new(planned: &[Duration], actual: &[Duration]) -> Self {
+            let mut splits = Vec::new();
+            let mut cumulative = 0.0;
+
+            for (planned, actual) in planned.iter().zip(actual.iter()) {
+                let delta = actual.as_secs_f32() - planned.as_secs_f32();
+                cumulative += delta;
+                splits.push(SplitDelta {
+                    planned: *planned,
+                    actual: *actual,
+                    delta,
+                    cumulative,
+                });
+            }
+
+            let mut plan_lost_at = None;
+            for (i, split) in splits.iter().enumerate() {
+                if split.cumulative > PLAN_TOLERANCE {
+                    if plan_lost_at.is_none() {
+                        plan_lost_at = Some(i);
+                    }
+                } else {
+                    plan_lost_at = None;
+                }
+            }
+
+            let compared = splits.len();
+            let elapsed: Duration = actual[..compared].iter().sum();
+            let remaining: Duration = planned[compared..].iter().sum();
+
+            let recent = compared.saturating_sub(TREND_SPLITS);
+            let recent_planned: f32 = planned[recent..compared].iter().map(|split| split.as_secs_f32()).sum();
+            let recent_actual: f32 = actual[recent..compared].iter().map(|split| split.as_secs_f32()).sum();
+            let trend = if recent_planned > 0.0 { recent_actual / recent_planned } else { 1.0 };
+
+            Comparison {
+                splits,
+                plan_lost_at,
+                projected_finish: elapsed + remaining.mul_f32(trend),
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::time::Duration;
+        use crate::running::{Race, Running, MetricRace, MetricRunning};
+        use crate::pacing::{PacingStrategy, Even};
+        use crate::comparison::Comparison;
+
+        fn splits(secs: &[u64]) -> Vec<Duration> {
+            secs.iter().map(|s| Duration::new(*s, 0)).collect()
+        }
+
+        #[test]
+        fn test_plan_lost() {
+            let planned = splits(&[300, 300, 300, 300, 300, 300]);
+            let actual = splits(&[295, 310, 298, 312, 315, 320]);
+            let comparison = Comparison::new(&planned, &actual);
+
+            // 10 s behind after the second split, back to 8 s, and lost from the fourth split on
+            assert_eq!(comparison.plan_lost_at, Some(3));
+            assert_eq!(comparison.projected_finish, Duration::new(1850, 0));
+        }
+
+        #[test]
+        fn test_projection_from_trend() {
+            let planned = splits(&[300, 300, 300, 300]);
+            let actual = splits(&[330, 330]);
+            let comparison = Comparison::new(&planned, &actual);
+
+            assert_eq!(comparison.splits.len(), 2);
+            assert_eq!(comparison.plan_lost_at, Some(0));
+            assert_eq!(comparison.projected_finish, Duration::new(1320, 0));
+        }
+
+        #[test]
+        fn test_no_actual_splits() {
+            let planned = splits(&[300, 300]);
+            let comparison = Comparison::new(&planned, &[]);
+
+            assert!(comparison.splits.is_empty());
+            assert_eq!(comparison.projected_finish, Duration::new(600, 0));
+        }
+
+        #[test]
+        fn test_plan_with_short_last_split() {
+            let race: MetricRace = Race::new(42195);
+            let running: MetricRunning = Running::new(Duration::new(14400, 0));
+            let planned = Even.splits(&running, &race);
+
+            assert_eq!(Comparison::new(&planned, &[]).projected_finish, running.duration());
+
+            let comparison = Comparison::new(&planned, &planned);
+            assert_eq!(comparison.splits[42].cumulative, 0.0);
+            assert!(!comparison.splits[42].is_ahead());
+            assert_eq!(comparison.projected_finish, running.duration());
+        }
+    }
+}
+
+/// API to follow the pace during a race.
+pub mod live {
+    use std::time::Duration;
+    use crate::running::Race;
+    use crate::running::Running;
+
+    /// The time buffer at an upcoming split marker.
+    pub struct Buffer {
+        pub distance: u64, // distance of the marker since the start, in the race scale
+        pub planned: Duration,
+        pub projected: Duration,
+        pub buffer: f32, // seconds, positive when ahead of the goal and negative when behind
+    }
+
+    /// Follows a race in progress towards the goal duration of a running,
+    /// updated by samples of elapsed time and distance covered.
+    pub struct LivePacer<'a, R: Race> {
+        race: &'a R,
+        goal: Duration,
+        elapsed: Duration,
+        distance: f32, // distance covered, in the race scale
+    }
+
+    impl<'a, R: Race> LivePacer<'a, R> {
+        /// Creates a live pacer to complete the race within the duration of the running.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use std::time::Duration;
+        /// use librunner::running::{Race, Running, MetricRace, MetricRunning};
+        /// use librunner::live::LivePacer;
+        ///
+        /// let race: MetricRace = Race::new(10000);
+        /// let running: MetricRunning = Running::new(Duration::new(3000, 0));
+        /// let mut pacer = LivePacer::new(&race, &running);
+        ///
+        /// pacer.update(Duration::new(1520, 0), 5000.0);
+        /// assert_eq!(pacer.required_pace(), Some(Duration::new(296, 0)));
+        /// assert_eq!(pacer.projected_finish(), Some(Duration::new(3040, 0)));
+        /// ```
+        pub fn new(race: &'a R, running: &impl Running) -> Self {
+            LivePacer {
+                race,
+                goal: running.duration(),
+                elapsed: Duration::ZERO,
+                distance: 0.0,
+            }
+        }
+
+        /// Updates the pacer with a new sample of elapsed time and distance covered, in the race scale.
+        /// Samples older than the last one are ignored.
+        pub fn update(&mut self, elapsed: Duration, distance: f32) {
+            if elapsed < self.elapsed || distance < self.distance {
+                return;
+            }
+
+            self.elapsed = elapsed;
+            self.distance = distance.min(self.race.distance() as f32);
+        }
+
+        /// Returns the distance left to the finish, in the race scale.
+        pub fn remaining_distance(&self) -> f32 {
+            self.race.distance() as f32 - self.distance
+        }
+
+        /// Calculates the average pace so far, or `None` before the first sample with distance.
+        pub fn average_pace(&self) -> Option<Duration> {
+            if self.distance <= 0.0 {
+                return None;
+            }
+
+            Some(self.elapsed.mul_f64(R::SPLIT_DISTANCE as f64 / self.distance as f64))
+        }
+
+        /// Calculates the pace required in the remaining distance to reach the goal,
+        /// or `None` when the race is over or the goal can't be reached anymore.
+        pub fn required_pace(&self) -> Option<Duration> {
+            let remaining = self.remaining_distance();
+            if remaining <= 0.0 || self.elapsed >= self.goal {
+                return None;
+            }
+
+            Some((self.goal - self.elapsed).mul_f64(R::SPLIT_DISTANCE as f64 / remaining as f64))
+        }
+
+        /// Projects the finish time keeping the average pace so far, or `None` before the first sample with distance.
+        pub fn projected_finish(&self) -> Option<Duration> {
+            let average_pace = self.average_pace()?;
+            let remaining = self.remaining_distance() as f64 / R::SPLIT_DISTANCE as f64;
+
+            Some(self.elapsed + average_pace.mul_f64(remaining))
+        }
+
+        /// Calculates the time buffer against the goal at each upcoming split marker, and at the finish,
+        /// comparing the time planned at even pace with the projected time at the average pace so far.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use std::time::Duration;
+        /// use librunner::running::{Race, Running, ImperialRace, ImperialRunning};
+        /// use librunner::live::LivePacer;
+        ///
+        /// let race: ImperialRace = Race::new(5280);
+        /// let running: ImperialRunning = Running::new(Duration::new(1800, 0));
+        /// let mut pacer = LivePacer::new(&race, &running);
+        ///
+        /// pacer.update(Duration::new(580, 0), 1760.0);
+        /// let buffers = pacer.buffers();
+        ///
+        /// assert_eq!(buffers.len(), 2);
+        /// assert_eq!(buffers[0].distance, 3520);
+        /// assert_eq!(buffers[0].buffer, 40.0);
+        /// ```
+        pub fn buffers(&self) -> Vec<Buffer> {
+            let mut buffers = Vec::new();
+            let average_pace = match self.average_pace() {
+                Some(pace) => pace.as_secs_f64() / R::SPLIT_DISTANCE as f64,
+                None => self.goal.as_secs_f64() / self.race.distance() as f64,
+            };
+
+            let mut marker = 0;
+            for distance in self.race.split_distances() {
+                marker += distance;
+                if marker as f32 <= self.distance {
+                    continue;
+                }
+
+                let planned = self.goal.mul_f64(marker as f64 / self.race.distance() as f64);
+                let projected = self.elapsed + Duration::from_secs_f64(average_pace * (marker as f64 - self.distance as f64));
+                buffers.push(Buffer {
+                    distance: marker,
+                    planned,
+                    projected,
+                    buffer: planned.as_secs_f32() - projected.as_secs_f32(),
+                });
+            }
+
+            buffers
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::time::Duration;
+        use crate::running::{Race, Running, MetricRace, MetricRunning};
+        use crate::live::LivePacer;
+
+        #[test]
+        fn test_before_first_sample() {
+            let race: MetricRace = Race::new(5000);
+            let running: MetricRunning = Running::new(Duration::new(1500, 0));
+            let pacer = LivePacer::new(&race, &running);
+
+            assert_eq!(pacer.average_pace(), None);
+            assert_eq!(pacer.projected_finish(), None);
+            assert_eq!(pacer.required_pace(), Some(Duration::new(300, 0)));
+            assert_eq!(pacer.buffers().len(), 5);
+        }
+
+        #[test]
+        fn test_incremental_samples() {
+            let race: MetricRace = Race::new(5000);
+            let running: MetricRunning = Running::new(Duration::new(1500, 0));
+            let mut pacer = LivePacer::new(&race, &running);
+
+            pacer.update(Duration::new(310, 0), 1000.0);
+            pacer.update(Duration::new(600, 0), 2000.0);
+            // an old sample arriving late
+            pacer.update(Duration::new(320, 0), 1050.0);
+
+            assert_eq!(pacer.average_pace(), Some(Duration::new(300, 0)));
+            assert_eq!(pacer.buffers()[0].buffer, 0.0);
+        }
+
+        #[test]
+        fn test_goal_out_of_reach() {
+            let race: MetricRace = Race::new(5000);
+            let running: MetricRunning = Running::new(Duration::new(1500, 0));
+            let mut pacer = LivePacer::new(&race, &running);
+
+            pacer.update(Duration::new(1500, 0), 4500.0);
+            assert_eq!(pacer.required_pace(), None);
+            assert_eq!(pacer.projected_finish().unwrap().as_secs(), 1666);
+        }
+    }
+}
+
+/// API to plan the schedules of pacer groups.
+pub mod pacer {
+    use std::time::Duration;
+    use crate::distance;
+    use crate::duration;
+    use crate::pace_band;
+    use crate::running::Race;
+
+    /// A water station where pacer groups slow down.
+    pub struct WaterStation {
+        pub distance: u64, // distance since the start, in the race scale
+        pub slowdown: Duration,
+    }
+
+    impl WaterStation {
+        pub fn new(distance: u64, slowdown: Duration) -> Self {
+            WaterStation {
+                distance,
+                slowdown,
+            }
+        }
+    }
+
+    /// The checkpoint schedule of every pacer group of a race.
+    pub struct Schedule {
+        pub unit: &'static str,
+        pub split_distance: u64,
+        pub checkpoints: Vec<u64>, // distances since the start, in the race scale
+        pub targets: Vec<Duration>,
+        pub times: Vec<Vec<Duration>>, // one list of checkpoint times per target
+    }
+
+    impl Schedule {
+        /// Creates the schedule of the pacer groups for the target finish times, with checkpoints at every split marker,
+        /// at the timing mats and at the finish. The time lost at the water stations is recovered by running the rest
+        /// of the race slightly faster, so every group still finishes on target.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use std::time::Duration;
+        /// use librunner::running::{Race, MetricRace};
+        /// use librunner::pacer::{Schedule, WaterStation};
+        /// use librunner::duration;
+        ///
+        /// let race: MetricRace = Race::new(42195);
+        /// let targets = vec![duration::to_duration(3, 0, 0), duration::to_duration(3, 30, 0)];
+        /// let mats = vec![21098];
+        /// let stations = vec![WaterStation::new(5000, Duration::new(10, 0))];
+        /// let schedule = Schedule::new(&race, &targets, &mats, &stations);
+        ///
+        /// // 42 split markers, the half marathon mat and the finish
+        /// assert_eq!(schedule.checkpoints.len(), 44);
+        /// assert_eq!(schedule.times[0][43], targets[0]);
+        /// assert_eq!(schedule.times[1][43], targets[1]);
+        /// ```
+        pub fn new<R: Race>(race: &R, targets: &[Duration], mats: &[u64], stations: &[WaterStation]) -> Self {
+            let mut checkpoints = Vec::new();
+            let mut marker = 0;
+            for distance in race.split_distances() {
+                marker += distance;
+                checkpoints.push(marker);
+            }
+            for mat in mats {
+                if *mat > 0 && *mat < race.distance() {
+                    checkpoints.push(*mat);
+                }
+            }
+            checkpoints.sort();
+            checkpoints.dedup();
+
+            // stations at the start or beyond the finish don't slow the groups down
+            let stations: Vec<&WaterStation> = stations.iter()
+                .filter(|station| station.distance > 0 && station.distance < race.distance())
+                .collect();
+            let slowdowns: Duration = stations.iter().map(|station| station.slowdown).sum();
+
+            let mut times = Vec::new();
+            for target in targets {
+                // pace in seconds per unit of distance, running between the water stations
+                let pace = target.saturating_sub(slowdowns).as_secs_f64() / race.distance() as f64;
+
+                let mut group = Vec::new();
+                for checkpoint in &checkpoints {
+                    let mut time = Duration::from_secs_f64(pace * *checkpoint as f64);
+                    for station in &stations {
+                        if station.distance < *checkpoint {
+                            time += station.slowdown;
+                        }
+                    }
+                    group.push(time);
+                }
+                times.push(group);
+            }
+
+            Schedule {
+                unit: pace_band::split_unit::<R>(),
+                split_distance: R::SPLIT_DISTANCE,
+                checkpoints,
+                targets: targets.to_vec(),
+                times,
+            }
+        }
+
+        /// Exports the schedule as a CSV table, with one row per checkpoint and one column per pacer group.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use librunner::running::{Race, ImperialRace};
+        /// use librunner::pacer::Schedule;
+        /// use librunner::duration;
+        ///
+        /// let race: ImperialRace = Race::new(3520);
+        /// let targets = vec![duration::to_duration(0, 14, 0), duration::to_duration(0, 16, 0)];
+        /// let schedule = Schedule::new(&race, &targets, &[], &[]);
+        ///
+        /// assert_eq!(schedule.to_csv(), "mi,14:00,16:00\n1,07:00,08:00\n2,14:00,16:00\n");
+        /// ```
+        pub fn to_csv(&self) -> String {
+            let mut csv = self.unit.to_string();
+            for target in &self.targets {
+                csv.push_str(&format!(",{}", duration::format_duration(*target)));
+            }
+            csv.push('\n');
+
+            for (i, checkpoint) in self.checkpoints.iter().enumerate() {
+                csv.push_str(&distance::format_distance(*checkpoint as f32 / self.split_distance as f32));
+                for group in &self.times {
+                    let time = Duration::new(group[i].as_secs_f64().round() as u64, 0);
+                    csv.push_str(&format!(",{}", duration::format_duration(time)));
+                }
+                csv.push('\n');
+            }
+
+            csv
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::time::Duration;
+        use crate::running::{Race, MetricRace};
+        use crate::pacer::{Schedule, WaterStation};
+
+        #[test]
+        fn test_water_station_slowdown() {
+            let race: MetricRace = Race::new(10000);
+            let targets = vec![Duration::new(3000, 0)];
+            let stations = vec![WaterStation::new(5000, Duration::new(20, 0))];
+            let schedule = Schedule::new(&race, &targets, &[], &stations);
+
+            // 2980 s of running at 298 s/km
+            assert_eq!(schedule.times[0][4].as_secs_f64().round(), 1490.0);
+            assert_eq!(schedule.times[0][5].as_secs_f64().round(), 1808.0);
+            assert_eq!(schedule.times[0][9].as_secs_f64().round(), 3000.0);
+        }
+
+        #[test]
+        fn test_stations_outside_the_course() {
+            let race: MetricRace = Race::new(10000);
+            let targets = vec![Duration::new(3000, 0)];
+            let stations = vec![
+                WaterStation::new(0, Duration::new(15, 0)),
+                WaterStation::new(10000, Duration::new(15, 0)),
+                WaterStation::new(12000, Duration::new(15, 0)),
+            ];
+            let schedule = Schedule::new(&race, &targets, &[], &stations);
+
+            assert_eq!(schedule.times[0][0], Duration::new(300, 0));
+            assert_eq!(schedule.times[0][9], Duration::new(3000, 0));
+        }
+
+        #[test]
+        fn test_mats_on_markers_are_not_repeated() {
+            let race: MetricRace = Race::new(21098);
+            let schedule = Schedule::new(&race, &[Duration::new(6000, 0)], &[10000, 15000, 30000], &[]);
+
+            assert_eq!(schedule.checkpoints.len(), 22);
+            assert!(schedule.to_csv().contains("\n21.1,"));
+        }
+    }
+}
+
+/// API to plan ultra-marathons around their aid stations and cutoffs.
+pub mod ultra {
+    use std::time::Duration;
+    use crate::pacing;
+    use crate::pacing::PacingStrategy;
+    use crate::running::Race;
+
+    /// An aid station along the course.
+    pub struct AidStation {
+        pub name: String,
+        pub distance: u64, // distance since the start, in the race scale
+        pub stop: Duration, // expected time spent at the station
+        pub cutoff: Option<Duration>, // latest time to leave the station since the start
+    }
+
+    impl AidStation {
+        pub fn new(name: &str, distance: u64, stop: Duration, cutoff: Option<Duration>) -> Self {
+            AidStation {
+                name: name.to_string(),
+                distance,
+                stop,
+                cutoff,
+            }
+        }
+    }
+
+    /// The plan to go through an aid station.
+    pub struct StationPlan {
+        pub name: String,
+        pub distance: u64,
+        pub arrival: Duration,
+        pub departure: Duration,
+        pub margin: Option<f32>, // seconds between the departure and the cutoff, negative when missing it
+        pub latest_safe_pace: Option<Duration>, // slowest pace from here that still makes every remaining cutoff
+    }
+
+    /// Plans the arrival and departure at each aid station to complete the race within the goal, including
+    /// the stops, running the rest of the time according to the pacing strategy. The stations must be sorted
+    /// by distance.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use librunner::running::{Race, MetricRace};
+    /// use librunner::pacing::Even;
+    /// use librunner::ultra::{self, AidStation};
+    /// use librunner::duration;
+    ///
+    /// let race: MetricRace = Race::new(50000);
+    /// let stations = vec![
+    ///     AidStation::new("Ridge", 20000, duration::to_duration(0, 5, 0), Some(duration::to_duration(3, 0, 0))),
+    ///     AidStation::new("Lake", 35000, duration::to_duration(0, 5, 0), Some(duration::to_duration(5, 0, 0))),
+    /// ];
+    /// let plan = ultra::plan(&race, &stations, duration::to_duration(5, 10, 0), &Even);
+    ///
+    /// // 5 hours of running at 6 minutes per km
+    /// assert_eq!(plan[0].arrival, duration::to_duration(2, 0, 0));
+    /// assert_eq!(plan[0].departure, duration::to_duration(2, 5, 0));
+    /// assert_eq!(plan[0].margin, Some(3300.0));
+    /// assert_eq!(plan[1].departure, duration::to_duration(3, 40, 0));
+    /// ```
+    pub fn plan<R: Race>(race: &R, stations: &[AidStation], goal: Duration, strategy: &impl PacingStrategy) -> Vec<StationPlan> {
+        let stops: Duration = stations.iter().map(|station| station.stop).sum();
+        let splits = strategy.distribute(race, goal.saturating_sub(stops));
+
+        let mut plans = Vec::new();
+        let mut stopped = Duration::ZERO;
+        for station in stations {
+            let arrival = pacing::elapsed_at(race, &splits, station.distance) + stopped;
+            let departure = arrival + station.stop;
+            stopped += station.stop;
+
+            plans.push(StationPlan {
+                name: station.name.clone(),
+                distance: station.distance,
+                arrival,
+                departure,
+                margin: station.cutoff.map(|cutoff| cutoff.as_secs_f32() - departure.as_secs_f32()),
+                latest_safe_pace: None,
+            });
+        }
+
+        for i in 0..plans.len() {
+            let departure = plans[i].departure.as_secs_f64();
+            plans[i].latest_safe_pace = safe_pace::<R>(&stations[i + 1..], stations[i].distance, departure);
+        }
+
+        plans
+    }
+
+    /// Calculates the slowest pace, per split distance, to leave every station before its cutoff when starting
+    /// the race, including the stops at the stations. Returns `None` when no station has a cutoff.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::MetricRace;
+    /// use librunner::ultra::{self, AidStation};
+    /// use librunner::duration;
+    ///
+    /// let stations = vec![
+    ///     AidStation::new("Ridge", 20000, duration::to_duration(0, 5, 0), Some(duration::to_duration(3, 0, 0))),
+    ///     AidStation::new("Lake", 35000, duration::to_duration(0, 5, 0), Some(duration::to_duration(5, 0, 0))),
+    /// ];
+    ///
+    /// // Lake is the tightest cutoff: 290 minutes of running for 35 km
+    /// let pace = ultra::latest_safe_pace::<MetricRace>(&stations).unwrap();
+    /// assert_eq!(pace.as_secs(), 497);
+    /// ```
+    pub fn latest_safe_pace<R: Race>(stations: &[AidStation]) -> Option<Duration> {
+        safe_pace::<R>(stations, 0, 0.0)
+    }
+
+    /// Calculates the slowest pace, per split distance, to leave each of the stations before their cutoffs,
+    /// leaving the distance at the departure time in seconds. Stations that are not ahead of the distance are
+    /// skipped, since no pace takes the runner to them.
+    fn safe_pace<R: Race>(stations: &[AidStation], from: u64, departure: f64) -> Option<Duration> {
+        let mut safe_pace: Option<f64> = None;
+        let mut stops = 0.0;
+
+        for station in stations {
+            stops += station.stop.as_secs_f64();
+            let cutoff = match station.cutoff {
+                Some(cutoff) => cutoff.as_secs_f64(),
+                None => continue,
+            };
+            let distance = match station.distance.checked_sub(from) {
+                Some(distance) if distance > 0 => distance as f64,
+                _ => continue,
+            };
+
+            let pace = ((cutoff - departure - stops) / distance).max(0.0);
+            safe_pace = Some(match safe_pace {
+                Some(safe_pace) => safe_pace.min(pace),
+                None => pace,
+            });
+        }
+
+        safe_pace.map(|pace| Duration::from_secs_f64(pace * R::SPLIT_DISTANCE as f64))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::time::Duration;
+        use crate::running::{Race, ImperialRace};
+        use crate::pacing::Positive;
+        use crate::ultra::{self, AidStation};
+        use crate::duration;
+
+        #[test]
+        fn test_latest_safe_pace() {
+            let race: ImperialRace = Race::new(176000); // 100 miles
+            let stations = vec![
+                AidStation::new("A", 44000, Duration::new(600, 0), None),
+                AidStation::new("B", 88000, Duration::new(600, 0), Some(duration::to_duration(14, 0, 0))),
+                AidStation::new("C", 132000, Duration::new(600, 0), Some(duration::to_duration(21, 0, 0))),
+            ];
+            let plan = ultra::plan(&race, &stations, duration::to_duration(24, 0, 0), &Positive::new(Duration::new(60, 0)));
+
+            assert!(plan[0].arrival < plan[1].arrival);
+            assert_eq!(plan[0].margin, None);
+            assert!(plan[1].margin.unwrap() > 0.0);
+            assert_eq!(plan[2].latest_safe_pace, None);
+
+            // leaving A, station C is the tightest cutoff, with two stops before leaving it
+            let departure = plan[0].departure.as_secs_f64();
+            let expected = (duration::to_duration(21, 0, 0).as_secs_f64() - 1200.0 - departure) / 50.0;
+            assert!((plan[0].latest_safe_pace.unwrap().as_secs_f64() - expected).abs() < 0.01);
+        }
+
+        #[test]
+        fn test_missed_cutoff() {
+            let race: ImperialRace = Race::new(88000);
+            let stations = vec![AidStation::new("A", 44000, Duration::ZERO, Some(duration::to_duration(5, 0, 0)))];
+            let plan = ultra::plan(&race, &stations, duration::to_duration(12, 0, 0), &crate::pacing::Even);
+
+            assert_eq!(plan[0].margin, Some(-3600.0));
+        }
+
+        #[test]
+        fn test_stations_at_the_same_distance_or_unsorted() {
+            let race: ImperialRace = Race::new(88000);
+            let cutoff = Some(duration::to_duration(5, 0, 0));
+            let stations = vec![
+                AidStation::new("A", 22000, Duration::ZERO, cutoff),
+                AidStation::new("B", 22000, Duration::ZERO, cutoff),
+                AidStation::new("C", 11000, Duration::ZERO, cutoff),
+            ];
+            let plan = ultra::plan(&race, &stations, duration::to_duration(12, 0, 0), &crate::pacing::Even);
+
+            assert_eq!(plan[0].latest_safe_pace, None);
+            assert_eq!(plan[1].latest_safe_pace, None);
+            assert_eq!(ultra::latest_safe_pace::<ImperialRace>(&stations), Some(Duration::new(1440, 0)));
+        }
+    }
+}
+
+/// API to plan relays, where a team of runners covers the legs of a race.
+pub mod relay {
+    use std::time::Duration;
+    use crate::running::Race;
+    use crate::running::Running;
+
+    /// A leg of the relay, assigned to a runner with an expected running.
+    pub struct Leg<R: Race, G: Running> {
+        pub runner: String,
+        pub race: R,
+        pub running: G, // expected running
+        pub actual: Option<Duration>,
+    }
+
+    impl<R: Race, G: Running> Leg<R, G> {
+        /// Creates a new leg of the relay.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use std::time::Duration;
+        /// use librunner::running::{Race, Running, MetricRace, MetricRunning};
+        /// use librunner::relay::Leg;
+        ///
+        /// let leg: Leg<MetricRace, MetricRunning> = Leg::new("Ana", Race::new(10000), Running::new(Duration::new(2400, 0)));
+        /// assert_eq!(leg.time(), Duration::new(2400, 0));
+        /// ```
+        pub fn new(runner: &str, race: R, running: G) -> Self {
+            Leg {
+                runner: runner.to_string(),
+                race,
+                running,
+                actual: None,
+            }
+        }
+
+        /// Returns the actual time of the leg, or the expected one if the leg wasn't run yet.
+        pub fn time(&self) -> Duration {
+            match self.actual {
+                Some(actual) => actual,
+                None => self.running.duration(),
+            }
+        }
+    }
+
+    /// A relay team, starting at a clock time.
+    pub struct Relay<R: Race, G: Running> {
+        pub start: Duration, // clock time since midnight
+        pub legs: Vec<Leg<R, G>>,
+    }
+
+    impl<R: Race, G: Running> Relay<R, G> {
+        /// Creates a new relay starting at a clock time, such as `duration::to_duration(7, 30, 0)` for 07:30.
+        pub fn new(start: Duration) -> Self {
+            Relay {
+                start,
+                legs: Vec::new(),
+            }
+        }
+
+        /// Adds a leg at the end of the relay.
+        pub fn add_leg(&mut self, leg: Leg<R, G>) {
+            self.legs.push(leg);
+        }
+
+        /// Records the actual time of a leg, which replaces its expected time in the plan.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the leg doesn't exist.
+        pub fn record(&mut self, leg: usize, actual: Duration) {
+            self.legs[leg].actual = Some(actual);
+        }
+
+        /// Calculates the total time of the team, with the actual time of the legs already run
+        /// and the expected time of the others.
+        pub fn total_time(&self) -> Duration {
+            self.legs.iter().map(|leg| leg.time()).sum()
+        }
+
+        /// Calculates the clock time of each handoff, the last one being the finish.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use std::time::Duration;
+        /// use librunner::running::{Race, Running, MetricRace, MetricRunning};
+        /// use librunner::relay::{Relay, Leg};
+        /// use librunner::duration;
+        ///
+        /// let mut relay: Relay<MetricRace, MetricRunning> = Relay::new(duration::to_duration(8, 0, 0));
+        /// relay.add_leg(Leg::new("Ana", Race::new(10000), Running::new(duration::to_duration(0, 40, 0))));
+        /// relay.add_leg(Leg::new("Bia", Race::new(5000), Running::new(duration::to_duration(0, 22, 0))));
+        ///
+        /// assert_eq!(relay.handoffs(), vec![duration::to_duration(8, 40, 0), duration::to_duration(9, 2, 0)]);
+        ///
+        /// relay.record(0, duration::to_duration(0, 42, 30));
+        /// assert_eq!(relay.handoffs()[1], duration::to_duration(9, 4, 30));
+        /// ```
+        pub fn handoffs(&self) -> Vec<Duration> {
+            let mut handoffs = Vec::new();
+            let mut clock = self.start;
+
+            for leg in &self.legs {
+                clock += leg.time();
+                handoffs.push(clock);
+            }
+
+            handoffs
+        }
+
+        /// Re-plans the legs not run yet to reach the goal of the team, sharing the time left between them
+        /// in proportion to their expected times. It returns the new target time of each leg, keeping the
+        /// actual time of the legs already run.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use std::time::Duration;
+        /// use librunner::running::{Race, Running, MetricRace, MetricRunning};
+        /// use librunner::relay::{Relay, Leg};
+        ///
+        /// let mut relay: Relay<MetricRace, MetricRunning> = Relay::new(Duration::ZERO);
+        /// relay.add_leg(Leg::new("Ana", Race::new(5000), Running::new(Duration::new(1200, 0))));
+        /// relay.add_leg(Leg::new("Bia", Race::new(5000), Running::new(Duration::new(1200, 0))));
+        /// relay.add_leg(Leg::new("Cris", Race::new(10000), Running::new(Duration::new(2400, 0))));
+        /// relay.record(0, Duration::new(1260, 0));
+        ///
+        /// let plan = relay.replan(Duration::new(4800, 0));
+        /// assert_eq!(plan, vec![Duration::new(1260, 0), Duration::new(1180, 0), Duration::new(2360, 0)]);
+        /// ```
+        pub fn replan(&self, goal: Duration) -> Vec<Duration> {
+            let mut run = Duration::ZERO;
+            let mut expected = Duration::ZERO;
+            for leg in &self.legs {
+                match leg.actual {
+                    Some(actual) => run += actual,
+                    None => expected += leg.running.duration(),
+                }
+            }
+
+            let ratio = if expected.is_zero() {
+                1.0
+            } else {
+                goal.saturating_sub(run).as_secs_f64() / expected.as_secs_f64()
+            };
+
+            let mut plan = Vec::new();
+            for leg in &self.legs {
+                match leg.actual {
+                    Some(actual) => plan.push(actual),
+                    None => plan.push(leg.running.duration().mul_f64(ratio)),
+                }
+            }
+
+            plan
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::time::Duration;
+        use crate::running::{Race, Running, ImperialRace, ImperialRunning};
+        use crate::relay::{Relay, Leg};
+
+        fn ekiden() -> Relay<ImperialRace, ImperialRunning> {
+            let mut relay = Relay::new(Duration::new(25200, 0));
+            for (runner, yards, secs) in [("A", 8800, 2100), ("B", 17600, 4500), ("C", 8800, 2200)] {
+                relay.add_leg(Leg::new(runner, Race::new(yards), Running::new(Duration::new(secs, 0))));
+            }
+            relay
+        }
+
+        #[test]
+        fn test_total_time() {
+            let mut relay = ekiden();
+            assert_eq!(relay.total_time(), Duration::new(8800, 0));
+
+            relay.record(1, Duration::new(4400, 0));
+            assert_eq!(relay.total_time(), Duration::new(8700, 0));
+            assert_eq!(relay.handoffs()[2], Duration::new(33900, 0));
+        }
+
+        #[test]
+        fn test_replan_when_every_leg_was_run() {
+            let mut relay = ekiden();
+            relay.record(0, Duration::new(2000, 0));
+            relay.record(1, Duration::new(4000, 0));
+            relay.record(2, Duration::new(2000, 0));
+
+            assert_eq!(relay.replan(Duration::new(7000, 0)).iter().sum::<Duration>(), Duration::new(8000, 0));
+        }
+    }
+}
+
+/// API to plan races alternating running and walking intervals.
+pub mod run_walk {
+    use std::time::Duration;
+    use crate::running::Race;
+
+    /// The kind of an interval.
+    #[derive(Debug, PartialEq)]
+    pub enum IntervalKind {
+        Run,
+        Walk,
+    }
+
+    /// An interval of the schedule.
+    pub struct Interval {
+        pub kind: IntervalKind,
+        pub start: Duration,  // time since the start of the race
+        pub duration: Duration,
+        pub distance: f32,    // distance covered at the end of the interval, in the race scale
+    }
+
+    /// A run-walk strategy, such as 4:1 minutes, with the paces per split distance of the race.
+    pub struct RunWalk {
+        pub run: Duration,  // duration of each running interval
+        pub walk: Duration, // duration of each walking interval
+        pub run_pace: Duration,
+        pub walk_pace: Duration,
+    }
+
+    impl RunWalk {
+        /// Creates a new run-walk strategy.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use librunner::running::{Race, MetricRace};
+        /// use librunner::run_walk::RunWalk;
+        /// use librunner::duration;
+        ///
+        /// // 4 minutes running at 6:00/km and 1 minute walking at 10:00/km
+        /// let run_walk = RunWalk::new(duration::to_duration(0, 4, 0), duration::to_duration(0, 1, 0),
+        ///                             duration::to_duration(0, 6, 0), duration::to_duration(0, 10, 0));
+        /// let race: MetricRace = Race::new(5000);
+        ///
+        /// assert_eq!(run_walk.walk_breaks(&race), 6);
+        /// assert_eq!(run_walk.finish_time(&race).as_secs_f64().round(), 1944.0); // 00:32:24
+        /// ```
+        pub fn new(run: Duration, walk: Duration, run_pace: Duration, walk_pace: Duration) -> Self {
+            RunWalk {
+                run,
+                walk,
+                run_pace,
+                walk_pace,
+            }
+        }
+
+        /// Creates a run-walk strategy with the running pace needed to complete the race within the goal.
+        /// The running pace can't be slower than the walking pace, even if the goal allows it.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use librunner::running::{Race, MetricRace};
+        /// use librunner::run_walk::RunWalk;
+        /// use librunner::duration;
+        ///
+        /// let race: MetricRace = Race::new(42195);
+        /// let run_walk = RunWalk::new_from_goal(&race, duration::to_duration(5, 0, 0),
+        ///                                       duration::to_duration(0, 4, 0), duration::to_duration(0, 1, 0),
+        ///                                       duration::to_duration(0, 10, 0));
+        ///
+        /// assert_eq!(run_walk.finish_time(&race).as_secs_f64().round(), 18000.0);
+        /// ```
+        pub fn new_from_goal<R: Race>(race: &R, goal: Duration, run: Duration, walk: Duration, walk_pace: Duration) -> Self {
+            let mut run_walk = RunWalk::new(run, walk, walk_pace, walk_pace);
+            let mut fastest = 0.0;
+            let mut slowest = walk_pace.as_secs_f64();
+
+            // the finish time grows with the running pace
+            for _n in 0..64 {
+                let pace = (fastest + slowest) / 2.0;
+                run_walk.run_pace = Duration::from_secs_f64(pace);
+                if run_walk.finish_time(race) > goal {
+                    slowest = pace;
+                } else {
+                    fastest = pace;
+                }
+            }
+            run_walk.run_pace = Duration::from_secs_f64(fastest);
+
+            run_walk
+        }
+
+        /// Returns the schedule of running and walking intervals to complete the race.
+        /// The last interval is shorter when the race ends before it does.
+        pub fn intervals<R: Race>(&self, race: &R) -> Vec<Interval> {
+            let distance = race.distance() as f32;
+            let run_speed = R::SPLIT_DISTANCE as f64 / self.run_pace.as_secs_f64();
+            let walk_speed = R::SPLIT_DISTANCE as f64 / self.walk_pace.as_secs_f64();
+
+            let mut intervals = Vec::new();
+            let mut start = Duration::ZERO;
+            let mut covered = 0.0;
+            let mut kind = IntervalKind::Run;
+
+            while covered < distance as f64 {
+                let (speed, length, next) = match kind {
+                    // without walking intervals, the runner runs until the finish
+                    IntervalKind::Run if self.walk.is_zero() => (run_speed, Duration::MAX, IntervalKind::Walk),
+                    IntervalKind::Run => (run_speed, self.run, IntervalKind::Walk),
+                    IntervalKind::Walk => (walk_speed, self.walk, IntervalKind::Run),
+                };
+
+                let remaining = distance as f64 - covered;
+                let duration = if speed * length.as_secs_f64() >= remaining {
+                    covered = distance as f64;
+                    Duration::from_secs_f64(remaining / speed)
+                } else {
+                    covered += speed * length.as_secs_f64();
+                    length
+                };
+
+                if !duration.is_zero() {
+                    intervals.push(Interval {
+                        kind,
+                        start,
+                        duration,
+                        distance: covered as f32,
+                    });
+                }
+                start += duration;
+                kind = next;
+            }
+
+            intervals
+        }
+
+        /// Calculates the time to complete the race.
+        pub fn finish_time<R: Race>(&self, race: &R) -> Duration {
+            let intervals = self.intervals(race);
+            match intervals.last() {
+                Some(interval) => interval.start + interval.duration,
+                None => Duration::ZERO,
+            }
+        }
+
+        /// Calculates the effective average pace, including the walking intervals.
+        pub fn average_pace<R: Race>(&self, race: &R) -> Duration {
+            self.finish_time(race).mul_f64(R::SPLIT_DISTANCE as f64 / race.distance() as f64)
+        }
+
+        /// Counts the walk breaks needed to complete the race.
+        pub fn walk_breaks<R: Race>(&self, race: &R) -> u64 {
+            self.intervals(race).iter().filter(|interval| interval.kind == IntervalKind::Walk).count() as u64
+        }
+
+        /// Returns the splits of the race, like `Running::splits`, with the time of the walking intervals
+        /// included in the splits where they happen.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use std::time::Duration;
+        /// use librunner::running::{Race, MetricRace};
+        /// use librunner::run_walk::RunWalk;
+        /// use librunner::duration;
+        ///
+        /// let run_walk = RunWalk::new(duration::to_duration(0, 5, 0), duration::to_duration(0, 1, 0),
+        ///                             duration::to_duration(0, 5, 0), duration::to_duration(0, 10, 0));
+        /// let race: MetricRace = Race::new(3000);
+        /// let splits = run_walk.splits(&race);
+        ///
+        /// assert_eq!(splits[0].as_secs_f64().round(), 300.0);
+        /// assert_eq!(splits[1].as_secs_f64().round(), 330.0); // 1 minute walking
+        /// assert_eq!(splits.iter().sum::<Duration>().as_secs_f64().round(), 960.0);
+        /// ```
+        pub fn splits<R: Race>(&self, race: &R) -> Vec<Duration> {
+            let intervals = self.intervals(race);
+
+            let mut splits = Vec::new();
+            let mut marker = 0;
+            let mut elapsed = Duration::ZERO;
+            for distance in race.split_distances() {
+                marker += distance;
+                let time = elapsed_at(&intervals, marker as f32);
+                splits.push(time - elapsed);
+                elapsed = time;
+            }
+
+            splits
+        }
+    }
+
+    /// Calculates the time since the start when the distance is reached.
+    fn elapsed_at(intervals: &[Interval], distance: f32) -> Duration {
+        let mut covered = 0.0;
+
+        for interval in intervals {
+            if interval.distance >= distance {
+                let fraction = (distance - covered) / (interval.distance - covered);
+                return interval.start + interval.duration.mul_f32(fraction);
+            }
+            covered = interval.distance;
+        }
+
+        match intervals.last() {
+            Some(interval) => interval.start + interval.duration,
+            None => Duration::ZERO,
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::time::Duration;
+        use crate::running::{Race, ImperialRace, MetricRace};
+        use crate::run_walk::{RunWalk, IntervalKind};
+        use crate::duration;
+
+        #[test]
+        fn test_intervals() {
+            let run_walk = RunWalk::new(Duration::new(240, 0), Duration::new(60, 0), Duration::new(360, 0), Duration::new(600, 0));
+            let race: MetricRace = Race::new(1000);
+            let intervals = run_walk.intervals(&race);
+
+            // 666.7 m running, 100 m walking and the rest running
+            assert_eq!(intervals.len(), 3);
+            assert_eq!(intervals[1].kind, IntervalKind::Walk);
+            assert_eq!(intervals[1].start.as_secs_f32().round(), 240.0);
+            assert_eq!(intervals[2].duration.as_secs_f32().round(), 84.0);
+        }
+
+        #[test]
+        fn test_imperial_average_pace() {
+            let run_walk = RunWalk::new(Duration::new(180, 0), Duration::new(30, 0), Duration::new(600, 0), Duration::new(900, 0));
+            let race: ImperialRace = Race::new(46112);
+            let average_pace = run_walk.average_pace(&race);
+
+            assert!(average_pace > Duration::new(600, 0));
+            assert!(average_pace < Duration::new(900, 0));
+        }
+
+        #[test]
+        fn test_no_walking() {
+            let run_walk = RunWalk::new(Duration::new(240, 0), Duration::ZERO, Duration::new(300, 0), Duration::new(600, 0));
+            let race: MetricRace = Race::new(5000);
+
+            assert_eq!(run_walk.walk_breaks(&race), 0);
+            assert_eq!(run_walk.finish_time(&race).as_secs_f64().round(), duration::to_duration(0, 25, 0).as_secs_f64());
+        }
+    }
+}
+
+/// API to time multisport events, such as duathlons and triathlons, with their run legs.
+pub mod multisport {
+    use std::time::Duration;
+    use crate::running::Race;
+    use crate::running::Running;
+
+    /// A segment of a multisport event.
+    pub enum Segment<R: Race, G: Running> {
+        Swim(Duration),
+        Bike(Duration),
+        Transition(Duration),
+        Run(R, G),
+    }
+
+    impl<R: Race, G: Running> Segment<R, G> {
+        /// Returns the duration of the segment.
+        pub fn duration(&self) -> Duration {
+            match self {
+                Segment::Swim(duration) | Segment::Bike(duration) | Segment::Transition(duration) => *duration,
+                Segment::Run(_, running) => running.duration(),
+            }
+        }
+    }
+
+    /// A multisport event, made of segments in the order they are done.
+    pub struct Event<R: Race, G: Running> {
+        pub start: Duration, // clock time since midnight
+        pub segments: Vec<Segment<R, G>>,
+    }
+
+    impl<R: Race, G: Running> Event<R, G> {
+        /// Creates a new event starting at a clock time, such as `duration::to_duration(7, 0, 0)` for 07:00.
+        pub fn new(start: Duration) -> Self {
+            Event {
+                start,
+                segments: Vec::new(),
+            }
+        }
+
+        /// Adds a segment at the end of the event.
+        pub fn add(&mut self, segment: Segment<R, G>) {
+            self.segments.push(segment);
+        }
+
+        /// Calculates the total time of the event.
+        pub fn total_time(&self) -> Duration {
+            self.segments.iter().map(|segment| segment.duration()).sum()
+        }
+
+        /// Calculates the clock time at the start of each transition.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use librunner::running::{Race, Running, MetricRace, MetricRunning};
+        /// use librunner::multisport::{Event, Segment};
+        /// use librunner::duration;
+        ///
+        /// let mut sprint: Event<MetricRace, MetricRunning> = Event::new(duration::to_duration(7, 0, 0));
+        /// sprint.add(Segment::Swim(duration::to_duration(0, 15, 0)));
+        /// sprint.add(Segment::Transition(duration::to_duration(0, 2, 0)));
+        /// sprint.add(Segment::Bike(duration::to_duration(0, 40, 0)));
+        /// sprint.add(Segment::Transition(duration::to_duration(0, 1, 0)));
+        /// sprint.add(Segment::Run(Race::new(5000), Running::new(duration::to_duration(0, 25, 0))));
+        ///
+        /// assert_eq!(sprint.total_time(), duration::to_duration(1, 23, 0));
+        /// assert_eq!(sprint.transitions(), vec![duration::to_duration(7, 15, 0), duration::to_duration(7, 57, 0)]);
+        /// ```
+        pub fn transitions(&self) -> Vec<Duration> {
+            let mut transitions = Vec::new();
+            let mut clock = self.start;
+
+            for segment in &self.segments {
+                if let Segment::Transition(_) = segment {
+                    transitions.push(clock);
+                }
+                clock += segment.duration();
+            }
+
+            transitions
+        }
+
+        /// Calculates the pace of each run leg to finish the event within the target, keeping the time of the other
+        /// segments and sharing the time left between the run legs in proportion to their current durations.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use librunner::running::{Race, Running, MetricRace, MetricRunning};
+        /// use librunner::multisport::{Event, Segment};
+        /// use librunner::duration;
+        ///
+        /// let mut duathlon: Event<MetricRace, MetricRunning> = Event::new(duration::to_duration(9, 0, 0));
+        /// duathlon.add(Segment::Run(Race::new(10000), Running::new(duration::to_duration(0, 50, 0))));
+        /// duathlon.add(Segment::Transition(duration::to_duration(0, 1, 0)));
+        /// duathlon.add(Segment::Bike(duration::to_duration(1, 20, 0)));
+        /// duathlon.add(Segment::Transition(duration::to_duration(0, 1, 0)));
+        /// duathlon.add(Segment::Run(Race::new(5000), Running::new(duration::to_duration(0, 25, 0))));
+        ///
+        /// // 70 minutes of running instead of 75
+        /// let paces = duathlon.run_paces(duration::to_duration(2, 32, 0));
+        /// assert_eq!(paces[0].as_secs_f64().round(), 280.0); // 04:40/km
+        /// assert_eq!(paces[1].as_secs_f64().round(), 280.0);
+        /// ```
+        pub fn run_paces(&self, target: Duration) -> Vec<Duration> {
+            let mut running = Duration::ZERO;
+            let mut others = Duration::ZERO;
+            for segment in &self.segments {
+                match segment {
+                    Segment::Run(_, leg) => running += leg.duration(),
+                    _ => others += segment.duration(),
+                }
+            }
+
+            let ratio = if running.is_zero() {
+                1.0
+            } else {
+                target.saturating_sub(others).as_secs_f64() / running.as_secs_f64()
+            };
+
+            let mut paces = Vec::new();
+            for segment in &self.segments {
+                if let Segment::Run(race, leg) = segment {
+                    let duration = leg.duration().mul_f64(ratio);
+                    paces.push(duration.mul_f64(R::SPLIT_DISTANCE as f64 / race.distance() as f64));
+                }
+            }
+
+            paces
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::time::Duration;
+        use crate::running::{Race, Running, ImperialRace, ImperialRunning};
+        use crate::multisport::{Event, Segment};
+
+        #[test]
+        fn test_event_without_transitions() {
+            let mut event: Event<ImperialRace, ImperialRunning> = Event::new(Duration::ZERO);
+            event.add(Segment::Bike(Duration::new(3600, 0)));
+            event.add(Segment::Run(Race::new(5280), Running::new(Duration::new(1800, 0))));
+
+            assert!(event.transitions().is_empty());
+            assert_eq!(event.total_time(), Duration::new(5400, 0));
+            assert_eq!(event.run_paces(Duration::new(5100, 0)), vec![Duration::new(500, 0)]);
+        }
+    }
+}
+
+/// API to estimate heart rates and calculate training zones.
+pub mod heart_rate {
+    use crate::running::Runner;
+
+    /// Fraction of the maximum heart rate used to estimate the lactate threshold heart rate when it is unknown.
+    pub const THRESHOLD_FRACTION: f32 = 0.89;
+
+    /// Heart rate data of a runner, in beats per minute. Unknown values are estimated when needed.
+    #[derive(Default, Clone, Copy, Debug, PartialEq)]
+    pub struct HeartRate {
+        pub max: Option<u64>,
+        pub resting: Option<u64>,
+        pub threshold: Option<u64>, // lactate threshold heart rate
+    }
+
+    /// Formulas to estimate the maximum heart rate from the age.
+    pub enum MaxFormula {
+        Tanaka, // 208 - 0.7 x age
+        Fox,    // 220 - age
+        Gulati, // 206 - 0.88 x age, for women
+    }
+
+    /// Systems to split the heart rate in training zones.
+    pub enum ZoneSystem {
+        PercentMax, // percentages of the maximum heart rate
+        Karvonen,   // percentages of the heart rate reserve, between resting and maximum
+        Threshold,  // percentages of the lactate threshold heart rate, as proposed by Joe Friel
+    }
+
+    /// A training zone, from `low` to `high` beats per minute.
+    #[derive(Debug, PartialEq)]
+    pub struct Zone {
+        pub name: &'static str,
+        pub low: u64,
+        pub high: u64,
+    }
+
+    /// Estimates the maximum heart rate from the age.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::heart_rate::{self, MaxFormula};
+    ///
+    /// assert_eq!(heart_rate::estimate_max(44, &MaxFormula::Tanaka), 177);
+    /// assert_eq!(heart_rate::estimate_max(44, &MaxFormula::Fox), 176);
+    /// assert_eq!(heart_rate::estimate_max(44, &MaxFormula::Gulati), 167);
+    /// ```
+    pub fn estimate_max(age: u64, formula: &MaxFormula) -> u64 {
+        let age = age as f32;
+        let max = match formula {
+            MaxFormula::Tanaka => 208.0 - 0.7 * age,
+            MaxFormula::Fox => 220.0 - age,
+            MaxFormula::Gulati => 206.0 - 0.88 * age,
+        };
+        max.round() as u64
+    }
+
+    /// Returns the maximum heart rate of the runner, estimated with the formula when it is unknown.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::{Runner, MetricRunner};
+    /// use librunner::heart_rate::{self, MaxFormula};
+    ///
+    /// let mut runner: MetricRunner = Runner::new(70.0, 1.75, 44);
+    /// assert_eq!(heart_rate::max(&runner, &MaxFormula::Tanaka), 177);
+    ///
+    /// runner.heart_rate.max = Some(185);
+    /// assert_eq!(heart_rate::max(&runner, &MaxFormula::Tanaka), 185);
+    /// ```
+    pub fn max(runner: &impl Runner, formula: &MaxFormula) -> u64 {
+        match runner.heart_rate().max {
+            Some(max) => max,
+            None => estimate_max(runner.age(), formula),
+        }
+    }
+
+    /// Returns the lactate threshold heart rate of the runner, estimated from the maximum heart rate when it is unknown.
+    pub fn threshold(runner: &impl Runner, formula: &MaxFormula) -> u64 {
+        match runner.heart_rate().threshold {
+            Some(threshold) => threshold,
+            None => (max(runner, formula) as f32 * THRESHOLD_FRACTION).round() as u64,
+        }
+    }
+
+    /// Splits the range from `base` x the first fraction to `base` x the last fraction in zones.
+    /// Zones too narrow for a whole beat, as with a base close to zero, are degenerate, with `high` not above `low`.
+    fn split(names: &[&'static str], fractions: &[f32], base: f32, offset: f32) -> Vec<Zone> {
+        let mut zones = Vec::new();
+
+        for (i, name) in names.iter().enumerate() {
+            let low = (offset + base * fractions[i]).round() as u64;
+            let high = (offset + base * fractions[i + 1]).round() as u64;
+            zones.push(Zone {
+                name,
+                low,
+                // zones don't overlap, except for the last one, which includes its upper limit
+                high: if i < names.len() - 1 { high.saturating_sub(1) } else { high },
+            });
+        }
+
+        zones
+    }
+
+    /// Calculates five zones from 50% to 100% of the maximum heart rate.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::heart_rate::{self, Zone};
+    ///
+    /// let zones = heart_rate::percent_max_zones(180);
+    /// assert_eq!(zones[0], Zone { name: "Z1", low: 90, high: 107 });
+    /// assert_eq!(zones[4], Zone { name: "Z5", low: 162, high: 180 });
+    /// ```
+    pub fn percent_max_zones(max: u64) -> Vec<Zone> {
+        split(&["Z1", "Z2", "Z3", "Z4", "Z5"], &[0.5, 0.6, 0.7, 0.8, 0.9, 1.0], max as f32, 0.0)
+    }
+
+    /// Calculates five zones from 50% to 100% of the heart rate reserve, added to the resting heart rate.
+    /// Returns `None` when the resting heart rate isn't below the maximum, since there is no reserve.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::heart_rate::{self, Zone};
+    ///
+    /// let zones = heart_rate::karvonen_zones(180, 60).unwrap();
+    /// assert_eq!(zones[0], Zone { name: "Z1", low: 120, high: 131 });
+    /// assert_eq!(zones[4], Zone { name: "Z5", low: 168, high: 180 });
+    ///
+    /// assert!(heart_rate::karvonen_zones(60, 60).is_none());
+    /// ```
+    pub fn karvonen_zones(max: u64, resting: u64) -> Option<Vec<Zone>> {
+        if resting >= max {
+            return None;
+        }
+
+        let reserve = (max - resting) as f32;
+        Some(split(&["Z1", "Z2", "Z3", "Z4", "Z5"], &[0.5, 0.6, 0.7, 0.8, 0.9, 1.0], reserve, resting as f32))
+    }
+
+    /// Calculates the seven running zones of Joe Friel from the lactate threshold heart rate.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::heart_rate::{self, Zone};
+    ///
+    /// let zones = heart_rate::threshold_zones(160);
+    /// assert_eq!(zones[1], Zone { name: "Z2", low: 136, high: 143 });
+    /// assert_eq!(zones[4], Zone { name: "Z5a", low: 160, high: 164 });
+    /// ```
+    pub fn threshold_zones(threshold: u64) -> Vec<Zone> {
+        split(&["Z1", "Z2", "Z3", "Z4", "Z5a", "Z5b", "Z5c"],
+              &[0.0, 0.85, 0.90, 0.95, 1.0, 1.03, 1.07, 1.10], threshold as f32, 0.0)
+    }
+
+    /// Calculates the training zones of the runner in the zone system, estimating the missing heart rates with the formula.
+    /// Returns `None` for the Karvonen system if the resting heart rate is unknown, since it can't be estimated,
+    /// or if it isn't below the maximum heart rate.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::{Runner, ImperialRunner};
+    /// use librunner::heart_rate::{self, MaxFormula, ZoneSystem};
+    ///
+    /// let mut runner: ImperialRunner = Runner::new(160.0, 68.0, 30);
+    /// assert!(heart_rate::zones(&runner, &ZoneSystem::Karvonen, &MaxFormula::Fox).is_none());
+    ///
+    /// runner.heart_rate.resting = Some(50);
+    /// let zones = heart_rate::zones(&runner, &ZoneSystem::Karvonen, &MaxFormula::Fox).unwrap();
+    /// assert_eq!(zones[4].high, 190);
+    /// ```
+    pub fn zones(runner: &impl Runner, system: &ZoneSystem, formula: &MaxFormula) -> Option<Vec<Zone>> {
+        match system {
+            ZoneSystem::PercentMax => Some(percent_max_zones(max(runner, formula))),
+            ZoneSystem::Karvonen => runner.heart_rate().resting.and_then(|resting| karvonen_zones(max(runner, formula), resting)),
+            ZoneSystem::Threshold => Some(threshold_zones(threshold(runner, formula))),
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::running::{Runner, MetricRunner};
+        use crate::heart_rate::{self, MaxFormula, ZoneSystem};
+
+        #[test]
+        fn test_zones_are_contiguous() {
+            for zones in [heart_rate::percent_max_zones(187), heart_rate::karvonen_zones(187, 48).unwrap(), heart_rate::threshold_zones(171)] {
+                for i in 1..zones.len() {
+                    assert_eq!(zones[i].low, zones[i - 1].high + 1);
+                }
+            }
+        }
+
+        #[test]
+        fn test_zones_without_heart_rate() {
+            let zones = heart_rate::percent_max_zones(0);
+            assert!(zones.iter().all(|zone| zone.low == 0 && zone.high == 0));
+
+            let mut runner: MetricRunner = Runner::new(60.0, 1.65, 40);
+            runner.heart_rate.max = Some(150);
+            runner.heart_rate.resting = Some(160);
+            assert!(heart_rate::zones(&runner, &ZoneSystem::Karvonen, &MaxFormula::Fox).is_none());
+        }
+
+        #[test]
+        fn test_estimated_threshold() {
+            let mut runner: MetricRunner = Runner::new(60.0, 1.65, 40);
+            assert_eq!(heart_rate::threshold(&runner, &MaxFormula::Fox), 160);
+
+            runner.heart_rate.threshold = Some(165);
+            let zones = heart_rate::zones(&runner, &ZoneSystem::Threshold, &MaxFormula::Fox).unwrap();
+            assert_eq!(zones[4].low, 165);
+        }
+    }
+}
+
+/// API to estimate the maximal oxygen uptake (VO2max) from field tests and races.
+pub mod vo2max {
+    use std::time::Duration;
+    use crate::distance;
+    use crate::running::Race;
+    use crate::running::Runner;
+    use crate::running::Running;
+    use crate::running::Sex;
+
+    /// Methods to estimate the VO2max.
+    #[derive(Debug, PartialEq)]
+    pub enum Method {
+        Cooper,         // distance covered in 12 minutes
+        OneAndHalfMile, // time to run 1.5 miles
+        Rockport,       // time to walk 1 mile and heart rate at the end
+        Race,           // VDOT of Jack Daniels from a race result
+    }
+
+    /// An estimate of the VO2max, in ml/kg/min.
+    #[derive(Debug, PartialEq)]
+    pub struct Estimate {
+        pub vo2max: f32,
+        pub method: Method,
+    }
+
+    /// Fitness categories by VO2max, age and sex.
+    #[derive(Debug, PartialEq)]
+    pub enum Category {
+        VeryPoor,
+        Poor,
+        Fair,
+        Good,
+        Excellent,
+        Superior,
+    }
+
+    /// Estimates the VO2max from the distance covered in the Cooper 12-minute test.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::{Race, MetricRace};
+    /// use librunner::vo2max::{self, Method};
+    ///
+    /// let covered: MetricRace = Race::new(2800);
+    /// let estimate = vo2max::cooper(&covered);
+    ///
+    /// assert_eq!(estimate.vo2max.round(), 51.0);
+    /// assert_eq!(estimate.method, Method::Cooper);
+    /// ```
+    pub fn cooper<R: Race>(covered: &R) -> Estimate {
+        let meters = covered.distance() as f32 * R::UNIT_IN_METERS;
+
+        Estimate {
+            vo2max: (meters - 504.9) / 44.73,
+            method: Method::Cooper,
+        }
+    }
+
+    /// Estimates the VO2max from the time to run 1.5 miles.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::vo2max;
+    /// use librunner::duration;
+    ///
+    /// let estimate = vo2max::one_and_half_mile(duration::to_duration(0, 10, 0));
+    /// assert_eq!(estimate.vo2max, 51.8);
+    /// ```
+    pub fn one_and_half_mile(time: Duration) -> Estimate {
+        Estimate {
+            vo2max: 483.0 / (time.as_secs_f32() / 60.0) + 3.5,
+            method: Method::OneAndHalfMile,
+        }
+    }
+
+    /// Estimates the VO2max from the Rockport test: the time to walk 1 mile as fast as possible
+    /// and the heart rate at the end of the walk. Returns `None` when the sex of the runner is unknown.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::{Runner, MetricRunner, Sex};
+    /// use librunner::vo2max;
+    /// use librunner::duration;
+    ///
+    /// let mut runner: MetricRunner = Runner::new(70.0, 1.75, 40);
+    /// assert_eq!(vo2max::rockport(&runner, duration::to_duration(0, 14, 0), 130), None);
+    ///
+    /// runner.sex = Some(Sex::Male);
+    /// let estimate = vo2max::rockport(&runner, duration::to_duration(0, 14, 0), 130).unwrap();
+    /// assert_eq!(estimate.vo2max.round(), 46.0);
+    /// ```
+    pub fn rockport(runner: &impl Runner, time: Duration, heart_rate: u64) -> Option<Estimate> {
+        let weight = runner.weight_in_kg() * distance::LBS_PER_KG;
+        let sex = match runner.sex()? {
+            Sex::Male => 1.0,
+            Sex::Female => 0.0,
+        };
+
+        Some(Estimate {
+            vo2max: 132.853 - 0.0769 * weight - 0.3877 * runner.age() as f32 + 6.315 * sex
+                - 3.2649 * time.as_secs_f32() / 60.0 - 0.1565 * heart_rate as f32,
+            method: Method::Rockport,
+        })
+    }
+
+    /// Estimates the VO2max from a race result, using the VDOT formula of Jack Daniels.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::{Race, Running, MetricRace, MetricRunning};
+    /// use librunner::vo2max;
+    /// use librunner::duration;
+    ///
+    /// let race: MetricRace = Race::new(10000);
+    /// let running: MetricRunning = Running::new(duration::to_duration(0, 40, 0));
+    /// assert_eq!(vo2max::race(&race, &running).vo2max.round(), 52.0);
+    /// ```
+    pub fn race<R: Race>(race: &R, running: &impl Running) -> Estimate {
+        let minutes = running.duration().as_secs_f32() / 60.0;
+        let velocity = race.distance() as f32 * R::UNIT_IN_METERS / minutes; // m/min
+
+        let vo2 = -4.60 + 0.182258 * velocity + 0.000104 * velocity * velocity;
+        let fraction = 0.8 + 0.1894393 * (-0.012778 * minutes).exp() + 0.2989558 * (-0.1932605 * minutes).exp();
+
+        Estimate {
+            vo2max: vo2 / fraction,
+            method: Method::Race,
+        }
+    }
+
+    /// Looks up the fitness category of the VO2max of the runner by age and sex.
+    /// Returns `None` when the sex of the runner is unknown.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::{Runner, MetricRunner, Sex};
+    /// use librunner::vo2max::{self, Category};
+    ///
+    /// let mut runner: MetricRunner = Runner::new(70.0, 1.75, 44);
+    /// assert_eq!(vo2max::category(&runner, 52.0), None);
+    ///
+    /// runner.sex = Some(Sex::Male);
+    /// assert_eq!(vo2max::category(&runner, 52.0), Some(Category::Superior));
+    /// ```
+    pub fn category(runner: &impl Runner, vo2max: f32) -> Option<Category> {
+        // lower limits of the categories poor, fair, good, excellent and superior
+        let limits = match (runner.sex()?, runner.age()) {
+            (Sex::Male, 0..=19) => [35.0, 38.4, 45.2, 51.0, 56.0],
+            (Sex::Male, 20..=29) => [33.0, 36.5, 42.5, 46.5, 52.5],
+            (Sex::Male, 30..=39) => [31.5, 35.5, 41.0, 45.0, 49.5],
+            (Sex::Male, 40..=49) => [30.2, 33.6, 39.0, 43.8, 48.1],
+            (Sex::Male, 50..=59) => [26.1, 31.0, 35.8, 41.0, 45.4],
+            (Sex::Male, _) => [20.5, 26.1, 32.3, 36.5, 44.3],
+            (Sex::Female, 0..=19) => [25.0, 31.0, 35.0, 39.0, 42.0],
+            (Sex::Female, 20..=29) => [23.6, 29.0, 33.0, 37.0, 41.1],
+            (Sex::Female, 30..=39) => [22.8, 27.0, 31.5, 35.7, 40.1],
+            (Sex::Female, 40..=49) => [21.0, 24.5, 29.0, 32.9, 37.0],
+            (Sex::Female, 50..=59) => [20.2, 22.8, 27.0, 31.5, 35.8],
+            (Sex::Female, _) => [17.5, 20.2, 24.5, 30.3, 31.5],
+        };
+
+        let category = if vo2max >= limits[4] {
+            Category::Superior
+        } else if vo2max >= limits[3] {
+            Category::Excellent
+        } else if vo2max >= limits[2] {
+            Category::Good
+        } else if vo2max >= limits[1] {
+            Category::Fair
+        } else if vo2max >= limits[0] {
+            Category::Poor
+        } else {
+            Category::VeryPoor
+        };
+
+        Some(category)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::time::Duration;
+        use crate::running::{Race, Running, ImperialRace, ImperialRunning, Runner, ImperialRunner, MetricRunner, Sex};
+        use crate::vo2max::{self, Category, Method};
+
+        #[test]
+        fn test_imperial_scale() {
+            let covered: ImperialRace = Race::new(3062); // 2800 m
+            assert_eq!(vo2max::cooper(&covered).vo2max.round(), 51.0);
+
+            let race: ImperialRace = Race::new(2640);
+            let running: ImperialRunning = Running::new(Duration::new(600, 0));
+            let estimate = vo2max::race(&race, &running);
+            assert_eq!(estimate.method, Method::Race);
+            assert_eq!(estimate.vo2max.round(), 45.0);
+        }
+
+        #[test]
+        fn test_rockport_for_imperial_runner() {
+            let mut runner: ImperialRunner = Runner::new(154.32, 68.9, 40);
+            runner.sex = Some(Sex::Female);
+            let estimate = vo2max::rockport(&runner, Duration::new(840, 0), 130).unwrap();
+            assert_eq!(estimate.vo2max.round(), 39.0);
+        }
+
+        #[test]
+        fn test_category_limits() {
+            let mut runner: MetricRunner = Runner::new(70.0, 1.75, 65);
+            runner.sex = Some(Sex::Male);
+            assert_eq!(vo2max::category(&runner, 20.4), Some(Category::VeryPoor));
+            assert_eq!(vo2max::category(&runner, 20.5), Some(Category::Poor));
+
+            let mut runner: MetricRunner = Runner::new(60.0, 1.65, 45);
+            runner.sex = Some(Sex::Female);
+            assert_eq!(vo2max::category(&runner, 37.0), Some(Category::Superior));
+            assert_eq!(vo2max::category(&runner, 30.0), Some(Category::Good));
+        }
+    }
+}
+
+/// API to estimate the energy spent by runners.
+pub mod energy {
+    use crate::distance;
+    use crate::running::Race;
+    use crate::running::Runner;
+    use crate::running::Running;
+    use crate::running::Sex;
+
+    /// Kilojoules in one kilocalorie.
+    pub const KJ_PER_KCAL: f32 = 4.184;
+
+    /// Kilocalories spent per liter of oxygen consumed.
+    pub const KCAL_PER_LITER_O2: f32 = 5.0;
+
+    /// Oxygen consumed at rest, in ml/kg/min, which is one metabolic equivalent (MET).
+    pub const RESTING_VO2: f32 = 3.5;
+
+    /// METs of running at speeds in miles per hour, from the Compendium of Physical Activities (2011).
+    const MET_TABLE: [(f32, f32); 15] = [
+        (4.0, 6.0), (5.0, 8.3), (5.2, 9.0), (6.0, 9.8), (6.7, 10.5), (7.0, 11.0), (7.5, 11.5), (8.0, 11.8),
+        (8.6, 12.3), (9.0, 12.8), (10.0, 14.5), (11.0, 16.0), (12.0, 19.0), (13.0, 19.8), (14.0, 23.0),
+    ];
+
+    /// Methods to estimate the energy spent running.
+    pub enum Method {
+        Acsm, // running equation of the American College of Sports Medicine
+        Met,  // metabolic equivalents from the Compendium of Physical Activities
+    }
+
+    /// An amount of energy.
+    #[derive(Debug, PartialEq)]
+    pub struct Energy {
+        pub kcal: f32,
+        pub kj: f32,
+    }
+
+    impl Energy {
+        /// Creates an amount of energy from kilocalories.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use librunner::energy::Energy;
+        ///
+        /// assert_eq!(Energy::new(100.0).kj, 418.4);
+        /// ```
+        pub fn new(kcal: f32) -> Self {
+            Energy {
+                kcal,
+                kj: kcal * KJ_PER_KCAL,
+            }
+        }
+    }
+
+    /// Calculates the oxygen consumption of running, in ml/kg/min, with the ACSM running equation.
+    /// The speed is in m/min and the grade is the rise over the distance, such as 0.05 for 5%.
+    /// Downhill grades count as flat, since the equation only applies to uphill running.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::energy;
+    ///
+    /// assert_eq!(energy::running_vo2(200.0, 0.0), 43.5);
+    /// assert_eq!(energy::running_vo2(200.0, 0.05), 52.5);
+    /// ```
+    pub fn running_vo2(speed: f32, grade: f32) -> f32 {
+        0.2 * speed + 0.9 * speed * grade.max(0.0) + RESTING_VO2
+    }
+
+    /// Looks up the METs of running on flat terrain at the speed in m/s, interpolating the Compendium table.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::energy;
+    ///
+    /// assert_eq!(energy::met(2.68224).round(), 10.0); // 6 mph
+    /// ```
+    pub fn met(speed: f32) -> f32 {
+        let mph = speed * 3600.0 / distance::METERS_IN_MILE;
+        let (first, last) = (MET_TABLE[0], MET_TABLE[MET_TABLE.len() - 1]);
+
+        if mph <= first.0 {
+            return first.1;
+        }
+
+        for i in 1..MET_TABLE.len() {
+            let (before, after) = (MET_TABLE[i - 1], MET_TABLE[i]);
+            if mph <= after.0 {
+                return before.1 + (after.1 - before.1) * (mph - before.0) / (after.0 - before.0);
+            }
+        }
+
+        last.1
+    }
+
+    /// Estimates the energy spent by the runner to complete the race in the running, on a course with
+    /// an average grade, such as 0.02 for 2%.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::{Race, Running, Runner, MetricRace, MetricRunning, MetricRunner};
+    /// use librunner::energy::{self, Method};
+    /// use librunner::duration;
+    ///
+    /// let runner: MetricRunner = Runner::new(70.0, 1.75, 35);
+    /// let race: MetricRace = Race::new(10000);
+    /// let running: MetricRunning = Running::new(duration::to_duration(0, 50, 0));
+    ///
+    /// let acsm = energy::expenditure(&runner, &race, &running, 0.0, &Method::Acsm);
+    /// assert_eq!(acsm.kcal.round(), 761.0);
+    ///
+    /// let met = energy::expenditure(&runner, &race, &running, 0.0, &Method::Met);
+    /// assert_eq!(met.kcal.round(), 668.0);
+    /// ```
+    pub fn expenditure<R: Race>(runner: &impl Runner, race: &R, running: &impl Running, grade: f32, method: &Method) -> Energy {
+        let minutes = running.duration().as_secs_f32() / 60.0;
+        let speed = race.distance() as f32 * R::UNIT_IN_METERS / minutes; // m/min
+        let weight = runner.weight_in_kg();
+
+        let kcal = match method {
+            Method::Acsm => running_vo2(speed, grade) * weight * minutes / 1000.0 * KCAL_PER_LITER_O2,
+            Method::Met => {
+                // the Compendium is for flat running, so the climb comes from the ACSM equation
+                let climb = 0.9 * speed * grade.max(0.0) / RESTING_VO2;
+                (met(speed / 60.0) + climb) * weight * minutes / 60.0
+            },
+        };
+
+        Energy::new(kcal)
+    }
+
+    /// Formulas to estimate the basal metabolic rate.
+    pub enum BmrFormula {
+        MifflinStJeor,  // from weight, height, age and sex
+        HarrisBenedict, // revised by Roza and Shizgal, from weight, height, age and sex
+        KatchMcArdle,   // from the lean body mass, when the body fat is known
+    }
+
+    /// Estimates the basal metabolic rate of the runner, the energy spent at rest in a day.
+    /// Returns `None` when the formula needs the sex or the body fat of the runner and it is unknown.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::{Runner, MetricRunner, Sex};
+    /// use librunner::energy::{self, BmrFormula};
+    ///
+    /// let mut runner: MetricRunner = Runner::new(70.0, 1.75, 35);
+    /// assert_eq!(energy::bmr(&runner, &BmrFormula::MifflinStJeor), None);
+    ///
+    /// runner.sex = Some(Sex::Male);
+    /// runner.body_fat = Some(15.0);
+    /// assert_eq!(energy::bmr(&runner, &BmrFormula::MifflinStJeor).unwrap().kcal.round(), 1624.0);
+    /// assert_eq!(energy::bmr(&runner, &BmrFormula::HarrisBenedict).unwrap().kcal.round(), 1667.0);
+    /// assert_eq!(energy::bmr(&runner, &BmrFormula::KatchMcArdle).unwrap().kcal.round(), 1655.0);
+    /// ```
+    pub fn bmr(runner: &impl Runner, formula: &BmrFormula) -> Option<Energy> {
+        let weight = runner.weight_in_kg();
+        let height = runner.height_in_m() * 100.0; // cm
+        let age = runner.age() as f32;
+
+        let kcal = match (formula, runner.sex()) {
+            (BmrFormula::MifflinStJeor, Some(Sex::Male)) => 10.0 * weight + 6.25 * height - 5.0 * age + 5.0,
+            (BmrFormula::MifflinStJeor, Some(Sex::Female)) => 10.0 * weight + 6.25 * height - 5.0 * age - 161.0,
+            (BmrFormula::HarrisBenedict, Some(Sex::Male)) => 88.362 + 13.397 * weight + 4.799 * height - 5.677 * age,
+            (BmrFormula::HarrisBenedict, Some(Sex::Female)) => 447.593 + 9.247 * weight + 3.098 * height - 4.330 * age,
+            (BmrFormula::KatchMcArdle, _) => {
+                let lean_mass = weight * (1.0 - runner.body_fat()? / 100.0);
+                370.0 + 21.6 * lean_mass
+            },
+            (_, None) => return None,
+        };
+
+        Some(Energy::new(kcal))
+    }
+
+    /// Estimates the total daily energy expenditure of the runner: the basal metabolic rate multiplied by the
+    /// activity level, which reflects the activity besides exercise, plus the energy spent in the runnings
+    /// logged that day above the resting energy, already counted in the basal metabolic rate. Returns `None`
+    /// when the basal metabolic rate can't be estimated.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::{Race, Running, Runner, MetricRace, MetricRunning, MetricRunner, Sex, ActivityLevel};
+    /// use librunner::energy::{self, BmrFormula};
+    /// use librunner::duration;
+    ///
+    /// let mut runner: MetricRunner = Runner::new(70.0, 1.75, 35);
+    /// runner.sex = Some(Sex::Male);
+    /// runner.activity_level = ActivityLevel::Light;
+    ///
+    /// let runnings: Vec<(MetricRace, MetricRunning)> = vec![
+    ///     (Race::new(10000), Running::new(duration::to_duration(0, 50, 0))),
+    /// ];
+    /// let daily = energy::daily_expenditure(&runner, &BmrFormula::MifflinStJeor, &runnings).unwrap();
+    /// assert_eq!(daily.kcal.round(), 2933.0);
+    /// ```
+    pub fn daily_expenditure<R: Race, G: Running>(runner: &impl Runner, formula: &BmrFormula, runnings: &[(R, G)]) -> Option<Energy> {
+        let mut kcal = bmr(runner, formula)?.kcal * runner.activity_level().factor();
+
+        for (race, running) in runnings {
+            // net energy, without the resting oxygen consumption of the ACSM equation
+            let minutes = running.duration().as_secs_f32() / 60.0;
+            let resting = RESTING_VO2 * runner.weight_in_kg() * minutes / 1000.0 * KCAL_PER_LITER_O2;
+            kcal += expenditure(runner, race, running, 0.0, &Method::Acsm).kcal - resting;
+        }
+
+        Some(Energy::new(kcal))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::running::{Race, Running, Runner, ImperialRace, ImperialRunning, ImperialRunner, MetricRace, MetricRunning, MetricRunner};
+        use crate::running::{Sex, ActivityLevel};
+        use crate::energy::{self, Method, BmrFormula};
+        use crate::duration;
+
+        #[test]
+        fn test_met_limits() {
+            assert_eq!(energy::met(1.0), 6.0);
+            assert_eq!(energy::met(10.0), 23.0);
+        }
+
+        #[test]
+        fn test_imperial_runner_spends_the_same() {
+            let m_runner: MetricRunner = Runner::new(70.0, 1.75, 35);
+            let i_runner: ImperialRunner = Runner::new(154.324, 68.9, 35);
+            let m_race: MetricRace = Race::new(16093);
+            let i_race: ImperialRace = Race::new(17600);
+            let m_running: MetricRunning = Running::new(duration::to_duration(1, 20, 0));
+            let i_running: ImperialRunning = Running::new(duration::to_duration(1, 20, 0));
+
+            let metric = energy::expenditure(&m_runner, &m_race, &m_running, 0.0, &Method::Acsm);
+            let imperial = energy::expenditure(&i_runner, &i_race, &i_running, 0.0, &Method::Acsm);
+            assert_eq!(metric.kcal.round(), imperial.kcal.round());
+        }
+
+        #[test]
+        fn test_uphill_costs_more() {
+            let runner: MetricRunner = Runner::new(60.0, 1.68, 28);
+            let race: MetricRace = Race::new(5000);
+            let running: MetricRunning = Running::new(duration::to_duration(0, 30, 0));
+
+            for method in [Method::Acsm, Method::Met] {
+                let flat = energy::expenditure(&runner, &race, &running, 0.0, &method);
+                let uphill = energy::expenditure(&runner, &race, &running, 0.04, &method);
+                let downhill = energy::expenditure(&runner, &race, &running, -0.04, &method);
+                assert!(uphill.kj > flat.kj);
+                assert_eq!(downhill, flat);
+            }
+        }
+
+        #[test]
+        fn test_bmr_for_imperial_runner() {
+            let mut runner: ImperialRunner = Runner::new(132.277, 64.96, 30);
+            runner.sex = Some(Sex::Female);
+
+            // 60 kg, 165 cm
+            assert_eq!(energy::bmr(&runner, &BmrFormula::MifflinStJeor).unwrap().kcal.round(), 1320.0);
+            assert_eq!(energy::bmr(&runner, &BmrFormula::KatchMcArdle), None);
+        }
+
+        #[test]
+        fn test_daily_expenditure_without_runnings() {
+            let mut runner: MetricRunner = Runner::new(60.0, 1.65, 30);
+            runner.sex = Some(Sex::Female);
+            runner.activity_level = ActivityLevel::Moderate;
+
+            let daily = energy::daily_expenditure::<MetricRace, MetricRunning>(&runner, &BmrFormula::MifflinStJeor, &[]).unwrap();
+            assert_eq!(daily.kcal.round(), (1320.25f32 * 1.55).round());
+        }
+    }
+}
+
+/// API to estimate the body composition of runners.
+pub mod body {
+    use crate::distance;
+    use crate::running::Runner;
+    use crate::running::Sex;
+
+    /// Centimeters in one inch.
+    const CM_PER_INCH: f32 = distance::METERS_IN_INCH * 100.0;
+
+    /// Cutoffs to classify the BMI.
+    pub enum BmiCutoffs {
+        Who,   // World Health Organization
+        Asian, // lower cutoffs recommended by the WHO for Asian populations
+    }
+
+    /// Categories of the BMI.
+    #[derive(Debug, PartialEq)]
+    pub enum BmiCategory {
+        Underweight,
+        Normal,
+        Overweight,
+        ObeseClassI,
+        ObeseClassII,
+        ObeseClassIII,
+    }
+
+    impl BmiCutoffs {
+        /// Returns the lower limits of the categories normal, overweight, obese class I, II and III.
+        fn limits(&self) -> [f32; 5] {
+            match self {
+                BmiCutoffs::Who => [18.5, 25.0, 30.0, 35.0, 40.0],
+                BmiCutoffs::Asian => [18.5, 23.0, 27.5, 32.5, 37.5],
+            }
+        }
+    }
+
+    /// Circumferences of the body, in centimeters, used to estimate the body fat.
+    pub struct Circumferences {
+        pub neck: f32,
+        pub waist: f32,
+        pub hip: Option<f32>, // needed for women
+    }
+
+    impl Circumferences {
+        /// Creates circumferences measured in centimeters.
+        pub fn new(neck: f32, waist: f32, hip: Option<f32>) -> Self {
+            Circumferences {
+                neck,
+                waist,
+                hip,
+            }
+        }
+
+        /// Creates circumferences measured in inches.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use librunner::body::Circumferences;
+        ///
+        /// let circumferences = Circumferences::new_from_inches(15.0, 32.0, None);
+        /// assert_eq!(circumferences.neck, 38.1);
+        /// ```
+        pub fn new_from_inches(neck: f32, waist: f32, hip: Option<f32>) -> Self {
+            Circumferences {
+                neck: neck * CM_PER_INCH,
+                waist: waist * CM_PER_INCH,
+                hip: hip.map(|hip| hip * CM_PER_INCH),
+            }
+        }
+    }
+
+    /// A weight in both scales.
+    #[derive(Debug, PartialEq)]
+    pub struct Weight {
+        pub kg: f32,
+        pub lbs: f32,
+    }
+
+    impl Weight {
+        /// Creates a weight from kilograms.
+        pub fn new(kg: f32) -> Self {
+            Weight {
+                kg,
+                lbs: kg * distance::LBS_PER_KG,
+            }
+        }
+    }
+
+    /// Classifies the BMI of the runner.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::{Runner, MetricRunner, ImperialRunner};
+    /// use librunner::body::{self, BmiCutoffs, BmiCategory};
+    ///
+    /// let m_runner: MetricRunner = Runner::new(85.0, 1.79, 44);
+    /// assert_eq!(body::bmi_category(&m_runner, &BmiCutoffs::Who), BmiCategory::Overweight);
+    ///
+    /// let i_runner: ImperialRunner = Runner::new(150.0, 68.0, 30);
+    /// assert_eq!(body::bmi_category(&i_runner, &BmiCutoffs::Who), BmiCategory::Normal);
+    /// assert_eq!(body::bmi_category(&i_runner, &BmiCutoffs::Asian), BmiCategory::Normal);
+    /// ```
+    pub fn bmi_category(runner: &impl Runner, cutoffs: &BmiCutoffs) -> BmiCategory {
+        let bmi = runner.bmi();
+        let limits = cutoffs.limits();
+
+        if bmi < limits[0] {
+            BmiCategory::Underweight
+        } else if bmi < limits[1] {
+            BmiCategory::Normal
+        } else if bmi < limits[2] {
+            BmiCategory::Overweight
+        } else if bmi < limits[3] {
+            BmiCategory::ObeseClassI
+        } else if bmi < limits[4] {
+            BmiCategory::ObeseClassII
+        } else {
+            BmiCategory::ObeseClassIII
+        }
+    }
+
+    /// Estimates the body fat of the runner in percent, with the circumference method of the US Navy.
+    /// Returns `None` if the sex of the runner is unknown, or if the runner is a woman and the hip is unknown.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::{Runner, MetricRunner, Sex};
+    /// use librunner::body::{self, Circumferences};
+    ///
+    /// let mut runner: MetricRunner = Runner::new(80.0, 1.80, 35);
+    /// runner.sex = Some(Sex::Male);
+    ///
+    /// let circumferences = Circumferences::new(38.0, 85.0, None);
+    /// assert_eq!(body::navy_body_fat(&runner, &circumferences).unwrap().round(), 16.0);
+    /// ```
+    pub fn navy_body_fat(runner: &impl Runner, circumferences: &Circumferences) -> Option<f32> {
+        let height = runner.height_in_m() * 100.0; // cm
+
+        let density = match runner.sex()? {
+            Sex::Male => 1.0324 - 0.19077 * (circumferences.waist - circumferences.neck).log10() + 0.15456 * height.log10(),
+            Sex::Female => {
+                let hip = circumferences.hip?;
+                1.29579 - 0.35004 * (circumferences.waist + hip - circumferences.neck).log10() + 0.22100 * height.log10()
+            },
+        };
+
+        Some(495.0 / density - 450.0)
+    }
+
+    /// Estimates the body fat of the runner in percent from the BMI, with the formula of Deurenberg for adults.
+    /// Returns `None` if the sex of the runner is unknown.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::{Runner, ImperialRunner, Sex};
+    /// use librunner::body;
+    ///
+    /// let mut runner: ImperialRunner = Runner::new(140.0, 65.0, 30);
+    /// runner.sex = Some(Sex::Female);
+    /// assert_eq!(body::deurenberg_body_fat(&runner).unwrap().round(), 29.0);
+    /// ```
+    pub fn deurenberg_body_fat(runner: &impl Runner) -> Option<f32> {
+        let sex = match runner.sex()? {
+            Sex::Male => 1.0,
+            Sex::Female => 0.0,
+        };
+
+        Some(1.2 * runner.bmi() + 0.23 * runner.age() as f32 - 10.8 * sex - 5.4)
+    }
+
+    /// Calculates the lean body mass of the runner with the body fat in percent.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::{Runner, MetricRunner};
+    /// use librunner::body;
+    ///
+    /// let runner: MetricRunner = Runner::new(80.0, 1.80, 35);
+    /// assert_eq!(body::lean_body_mass(&runner, 15.0).kg, 68.0);
+    /// ```
+    pub fn lean_body_mass(runner: &impl Runner, body_fat: f32) -> Weight {
+        Weight::new(runner.weight_in_kg() * (1.0 - body_fat / 100.0))
+    }
+
+    /// Calculates the range of weights with a normal BMI for the height of the runner.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::{Runner, MetricRunner};
+    /// use librunner::body::{self, BmiCutoffs};
+    ///
+    /// let runner: MetricRunner = Runner::new(85.0, 1.79, 44);
+    /// let (lowest, highest) = body::healthy_weight(&runner, &BmiCutoffs::Who);
+    /// assert_eq!(lowest.kg.round(), 59.0);
+    /// assert_eq!(highest.kg.round(), 80.0);
+    /// ```
+    pub fn healthy_weight(runner: &impl Runner, cutoffs: &BmiCutoffs) -> (Weight, Weight) {
+        let height = runner.height_in_m();
+        let limits = cutoffs.limits();
+
+        (Weight::new(limits[0] * height * height), Weight::new(limits[1] * height * height))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::running::{Runner, ImperialRunner, MetricRunner, Sex};
+        use crate::body::{self, BmiCutoffs, BmiCategory, Circumferences};
+
+        #[test]
+        fn test_asian_cutoffs_are_lower() {
+            let runner: MetricRunner = Runner::new(72.0, 1.70, 40); // BMI 24.9
+            assert_eq!(body::bmi_category(&runner, &BmiCutoffs::Who), BmiCategory::Normal);
+            assert_eq!(body::bmi_category(&runner, &BmiCutoffs::Asian), BmiCategory::Overweight);
+        }
+
+        #[test]
+        fn test_navy_body_fat_for_imperial_runner() {
+            let mut runner: ImperialRunner = Runner::new(140.0, 65.0, 30);
+            let circumferences = Circumferences::new_from_inches(13.0, 28.0, Some(38.0));
+            assert_eq!(body::navy_body_fat(&runner, &circumferences), None);
+
+            runner.sex = Some(Sex::Female);
+            let body_fat = body::navy_body_fat(&runner, &circumferences).unwrap();
+            assert!(body_fat > 20.0 && body_fat < 30.0);
+
+            let without_hip = Circumferences::new_from_inches(13.0, 28.0, None);
+            assert_eq!(body::navy_body_fat(&runner, &without_hip), None);
+        }
+
+        #[test]
+        fn test_healthy_weight_in_pounds() {
+            let runner: ImperialRunner = Runner::new(150.0, 68.0, 30);
+            let (lowest, highest) = body::healthy_weight(&runner, &BmiCutoffs::Who);
+            assert_eq!(lowest.lbs.round(), 122.0);
+            assert_eq!(highest.lbs.round(), 164.0);
+        }
+    }
+}
+
+/// API to estimate sweat losses and plan the hydration during races.
+pub mod hydration {
+    use std::time::Duration;
+    use crate::pacing;
+    use crate::pacing::PacingStrategy;
+    use crate::running::Race;
+    use crate::running::Running;
+
+    /// Highest fluid intake considered safe during a race, in liters per hour.
+    pub const MAX_INTAKE_RATE: f32 = 0.8;
+
+    /// Fraction of the sweat losses replaced by the hydration plan.
+    pub const REPLACEMENT: f32 = 0.8;
+
+    /// Highest volume considered safe to drink at once at an aid station, in milliliters.
+    pub const MAX_DRINK: f32 = 300.0;
+
+    /// Calculates the sweat rate in liters per hour from the weight before and after a running, in kilograms,
+    /// the fluid drunk and the urine passed during the running, in liters.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::{Running, MetricRunning};
+    /// use librunner::hydration;
+    /// use librunner::duration;
+    ///
+    /// let long_run: MetricRunning = Running::new(duration::to_duration(2, 0, 0));
+    /// let sweat_rate = hydration::sweat_rate(&long_run, 70.0, 68.6, 0.8, 0.2);
+    /// assert_eq!(sweat_rate.round(), 1.0);
+    /// ```
+    pub fn sweat_rate(running: &impl Running, weight_before: f32, weight_after: f32, fluid: f32, urine: f32) -> f32 {
+        // one kilogram of weight lost is about one liter of sweat
+        let sweat = weight_before - weight_after + fluid - urine;
+        let hours = running.duration().as_secs_f32() / 3600.0;
+        (sweat / hours).max(0.0)
+    }
+
+    /// The fluid to drink at an aid station.
+    pub struct Drink {
+        pub distance: u64, // distance of the aid station since the start, in the race scale
+        pub arrival: Duration,
+        pub volume: f32,    // milliliters
+        pub shortfall: f32, // milliliters needed until the next station above the safe volume to drink at once
+    }
+
+    /// Plans the fluid to drink at each aid station to run the race in the goal running, replacing part of the
+    /// sweat losses of the sweat rate, in liters per hour, without exceeding the safe intake rate. Each station
+    /// covers the fluid needed until the next station, or until the finish for the last one, up to the safe volume
+    /// to drink at once. The rest is reported as a shortfall, which calls for more stations or carrying fluid.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::{Race, Running, MetricRace, MetricRunning};
+    /// use librunner::hydration;
+    /// use librunner::duration;
+    ///
+    /// let race: MetricRace = Race::new(42195);
+    /// let running: MetricRunning = Running::new(duration::to_duration(4, 0, 0));
+    /// let stations = vec![5000, 7500, 10000, 12500, 15000, 17500, 20000, 22500, 25000, 27500, 30000, 32500, 35000,
+    ///                     37500, 40000];
+    /// let plan = hydration::plan(&race, &running, 1.2, &stations);
+    ///
+    /// // 0.8 l/h, which is the safe limit, instead of 0.96 l/h
+    /// assert_eq!(plan.len(), 15);
+    /// assert_eq!(plan[1].volume.round(), 190.0);
+    /// assert_eq!(plan.iter().map(|drink| drink.volume).sum::<f32>().round(), 2821.0);
+    ///
+    /// // a single station can't cover the whole race
+    /// let plan = hydration::plan(&race, &running, 1.2, &[5000]);
+    /// assert_eq!(plan[0].volume, 300.0);
+    /// assert_eq!(plan[0].shortfall.round(), 2521.0);
+    /// ```
+    pub fn plan<R: Race>(race: &R, running: &impl Running, sweat_rate: f32, stations: &[u64]) -> Vec<Drink> {
+        let splits = pacing::Even.splits(running, race);
+        let rate = (sweat_rate * REPLACEMENT).min(MAX_INTAKE_RATE) * 1000.0 / 3600.0; // ml/s
+
+        let mut drinks = Vec::new();
+        for (i, station) in stations.iter().enumerate() {
+            let next = match stations.get(i + 1) {
+                Some(next) => *next,
+                None => race.distance(),
+            };
+
+            let arrival = pacing::elapsed_at(race, &splits, *station);
+            let until_next = pacing::elapsed_at(race, &splits, next).saturating_sub(arrival);
+            let needed = rate * until_next.as_secs_f32();
+            drinks.push(Drink {
+                distance: *station,
+                arrival,
+                volume: needed.min(MAX_DRINK),
+                shortfall: (needed - MAX_DRINK).max(0.0),
+            });
+        }
+
+        drinks
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::time::Duration;
+        use crate::running::{Race, Running, ImperialRace, ImperialRunning, MetricRunning};
+        use crate::hydration;
+
+        #[test]
+        fn test_no_negative_sweat_rate() {
+            let running: MetricRunning = Running::new(Duration::new(3600, 0));
+            assert_eq!(hydration::sweat_rate(&running, 70.0, 71.0, 0.5, 0.0), 0.0);
+        }
+
+        #[test]
+        fn test_plan_below_safe_limit() {
+            let race: ImperialRace = Race::new(23056); // half marathon
+            let running: ImperialRunning = Running::new(Duration::new(7200, 0));
+            let plan = hydration::plan(&race, &running, 0.5, &[3520, 7040, 10560, 14080, 17600, 21120]);
+
+            // 0.4 l/h for the 7200 s left from the first station
+            let total: f32 = plan.iter().map(|drink| drink.volume).sum();
+            let from_first = 7200.0 - plan[0].arrival.as_secs_f32();
+            assert!((total - 0.4 * 1000.0 * from_first / 3600.0).abs() < 0.1);
+            assert!(plan[0].arrival < plan[1].arrival);
+            assert!(plan.iter().all(|drink| drink.shortfall == 0.0));
+        }
+    }
+}
+
+/// API to plan the carbohydrate and sodium intake during races.
+pub mod fueling {
+    use std::time::Duration;
+    use crate::pacing;
+    use crate::pacing::PacingStrategy;
+    use crate::running::Race;
+    use crate::running::Runner;
+    use crate::running::Running;
+
+    /// Sodium recommended per hour in races longer than an hour, in milligrams.
+    pub const SODIUM_RATE: f32 = 500.0;
+
+    /// Carbohydrates per hour, in grams, above which a mix of glucose and fructose is needed.
+    pub const SINGLE_CARBOHYDRATE_TOLERANCE: f32 = 60.0;
+
+    /// Carbohydrates per hour, in grams, above which most guts can't absorb them.
+    pub const CARBOHYDRATE_TOLERANCE: f32 = 90.0;
+
+    /// Carbohydrates per hour for each kilogram of weight, in grams, above which lighter runners may have gut issues.
+    pub const CARBOHYDRATE_PER_KG_TOLERANCE: f32 = 1.0;
+
+    /// Sodium per hour, in milligrams, above the typical range.
+    pub const SODIUM_TOLERANCE: f32 = 1000.0;
+
+    /// A gel or drink taken during the race.
+    pub struct Gel {
+        pub carbohydrate: f32, // grams
+        pub sodium: f32,       // milligrams
+    }
+
+    impl Gel {
+        pub fn new(carbohydrate: f32, sodium: f32) -> Self {
+            Gel {
+                carbohydrate,
+                sodium,
+            }
+        }
+    }
+
+    /// A gel taken at the end of a split.
+    pub struct Intake {
+        pub split: usize, // index of the split
+        pub elapsed: Duration,
+    }
+
+    /// Warnings about an intake above what the gut typically tolerates.
+    #[derive(Debug, PartialEq)]
+    pub enum Warning {
+        NeedsGlucoseAndFructose, // above 60 g/h of carbohydrates
+        AboveCarbohydrateTolerance, // above 90 g/h of carbohydrates
+        AboveWeightTolerance, // above 1 g/kg/h of carbohydrates
+        AboveSodiumTolerance, // above 1000 mg/h of sodium
+    }
+
+    /// The fueling plan of a race.
+    pub struct Plan {
+        pub carbohydrate_rate: f32, // grams per hour needed
+        pub sodium_rate: f32,       // milligrams per hour needed
+        pub intakes: Vec<Intake>,
+        pub warnings: Vec<Warning>,
+    }
+
+    /// Returns the carbohydrates needed per hour, in grams, for a race of the duration,
+    /// following the guidelines of Jeukendrup (2014).
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::fueling;
+    /// use librunner::duration;
+    ///
+    /// assert_eq!(fueling::carbohydrate_rate(duration::to_duration(0, 40, 0)), 0.0);
+    /// assert_eq!(fueling::carbohydrate_rate(duration::to_duration(1, 40, 0)), 30.0);
+    /// assert_eq!(fueling::carbohydrate_rate(duration::to_duration(3, 30, 0)), 90.0);
+    /// ```
+    pub fn carbohydrate_rate(duration: Duration) -> f32 {
+        let minutes = duration.as_secs() / 60;
+
+        match minutes {
+            0..=44 => 0.0,
+            45..=74 => 15.0, // small amounts or a mouth rinse
+            75..=119 => 30.0,
+            120..=149 => 60.0,
+            _ => 90.0,
+        }
+    }
+
+    /// Plans the gels to take at the end of the splits for the runner to complete the race in the running,
+    /// taking one before the finish whenever the carbohydrates needed so far reach the carbohydrates of a gel. The carbohydrates
+    /// per hour come from the guidelines unless the runner chooses a rate, and warnings are raised when the
+    /// planned intake exceeds the typical gut tolerance.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::{Race, Running, Runner, MetricRace, MetricRunning, MetricRunner};
+    /// use librunner::fueling::{self, Gel, Warning};
+    /// use librunner::duration;
+    ///
+    /// let runner: MetricRunner = Runner::new(70.0, 1.75, 35);
+    /// let race: MetricRace = Race::new(42195);
+    /// let running: MetricRunning = Running::new(duration::to_duration(3, 30, 0));
+    /// let plan = fueling::plan(&runner, &race, &running, &Gel::new(25.0, 100.0), None);
+    ///
+    /// assert_eq!(plan.carbohydrate_rate, 90.0);
+    /// assert_eq!(plan.sodium_rate, 500.0);
+    /// assert_eq!(plan.intakes.len(), 12);
+    /// assert_eq!(plan.warnings, vec![Warning::NeedsGlucoseAndFructose, Warning::AboveWeightTolerance]);
+    /// ```
+    pub fn plan<R: Race>(runner: &impl Runner, race: &R, running: &impl Running, gel: &Gel, rate: Option<f32>) -> Plan {
+        let duration = running.duration();
+        let hours = duration.as_secs_f32() / 3600.0;
+        let carbohydrate_rate = rate.unwrap_or_else(|| carbohydrate_rate(duration));
+        let sodium_rate = if hours > 1.0 { SODIUM_RATE } else { 0.0 };
+
+        let splits = pacing::Even.splits(running, race);
+        let mut intakes = Vec::new();
+        let mut covered = 0;
+        let mut taken = 0.0;
+        for (i, distance) in race.split_distances().iter().enumerate() {
+            covered += distance;
+            let elapsed = pacing::elapsed_at(race, &splits, covered);
+            // a gel at the finish line is no longer needed
+            if elapsed >= duration {
+                break;
+            }
+
+            let needed = carbohydrate_rate * elapsed.as_secs_f32() / 3600.0;
+            while gel.carbohydrate > 0.0 && needed - taken >= gel.carbohydrate {
+                intakes.push(Intake {
+                    split: i,
+                    elapsed,
+                });
+                taken += gel.carbohydrate;
+            }
+        }
+
+        let mut warnings = Vec::new();
+        let carbohydrate_intake = intakes.len() as f32 * gel.carbohydrate / hours;
+        let sodium_intake = intakes.len() as f32 * gel.sodium / hours;
+        if carbohydrate_intake > CARBOHYDRATE_TOLERANCE {
+            warnings.push(Warning::AboveCarbohydrateTolerance);
+        } else if carbohydrate_intake > SINGLE_CARBOHYDRATE_TOLERANCE {
+            warnings.push(Warning::NeedsGlucoseAndFructose);
+        }
+        if carbohydrate_intake > CARBOHYDRATE_PER_KG_TOLERANCE * runner.weight_in_kg() {
+            warnings.push(Warning::AboveWeightTolerance);
+        }
+        if sodium_intake > SODIUM_TOLERANCE {
+            warnings.push(Warning::AboveSodiumTolerance);
+        }
+
+        Plan {
+            carbohydrate_rate,
+            sodium_rate,
+            intakes,
+            warnings,
+        }
+    }
+
+    /// Shortest race, in minutes, that benefits from carbohydrate loading.
+    pub const LOADING_THRESHOLD: u64 = 90;
+
+    /// The carbohydrate target of a day before the race.
+    pub struct LoadingDay {
+        pub days_before: u64,
+        pub carbohydrate: f32, // grams
+        pub per_kg: f32,       // grams for each kilogram of weight
+    }
+
+    /// Plans the carbohydrate loading of the runner in the days before the race, expected to take the duration of
+    /// the running. Only the duration matters, since the glycogen spent depends on the time running. Races shorter
+    /// than 90 minutes don't need loading, so the plan is empty. Longer races load for one to three days, from 10 to
+    /// 12 g of carbohydrates for each kilogram of weight a day as the race gets longer.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::{Running, Runner, MetricRunning, MetricRunner};
+    /// use librunner::fueling;
+    /// use librunner::duration;
+    ///
+    /// let runner: MetricRunner = Runner::new(70.0, 1.75, 35);
+    /// let marathon: MetricRunning = Running::new(duration::to_duration(4, 0, 0));
+    /// let plan = fueling::loading_plan(&runner, &marathon);
+    ///
+    /// assert_eq!(plan.len(), 3);
+    /// assert_eq!(plan[0].days_before, 3);
+    /// assert_eq!(plan[0].carbohydrate, 840.0);
+    ///
+    /// let ten_k: MetricRunning = Running::new(duration::to_duration(0, 50, 0));
+    /// assert!(fueling::loading_plan(&runner, &ten_k).is_empty());
+    /// ```
+    pub fn loading_plan(runner: &impl Runner, running: &impl Running) -> Vec<LoadingDay> {
+        let minutes = running.duration().as_secs() / 60;
+        if minutes < LOADING_THRESHOLD {
+            return Vec::new();
+        }
+
+        let days = match minutes {
+            0..=119 => 1,
+            120..=179 => 2,
+            _ => 3,
+        };
+        let per_kg = 10.0 + 2.0 * ((minutes - LOADING_THRESHOLD) as f32 / 90.0).min(1.0);
+
+        let mut plan = Vec::new();
+        for days_before in (1..=days).rev() {
+            plan.push(LoadingDay {
+                days_before,
+                carbohydrate: per_kg * runner.weight_in_kg(),
+                per_kg,
+            });
+        }
+
+        plan
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::time::Duration;
+        use crate::running::{Race, Running, Runner, ImperialRace, ImperialRunning, ImperialRunner, MetricRace, MetricRunning, MetricRunner};
+        use crate::fueling::{self, Gel, Warning};
+
+        #[test]
+        fn test_short_race_needs_no_gels() {
+            let runner: MetricRunner = Runner::new(60.0, 1.70, 30);
+            let race: MetricRace = Race::new(10000);
+            let running: MetricRunning = Running::new(Duration::new(2400, 0));
+            let plan = fueling::plan(&runner, &race, &running, &Gel::new(25.0, 50.0), None);
+
+            assert!(plan.intakes.is_empty());
+            assert_eq!(plan.sodium_rate, 0.0);
+            assert!(plan.warnings.is_empty());
+        }
+
+        #[test]
+        fn test_chosen_rate_above_tolerance() {
+            let runner: ImperialRunner = Runner::new(176.0, 70.0, 40);
+            let race: ImperialRace = Race::new(46112);
+            let running: ImperialRunning = Running::new(Duration::new(14400, 0));
+            let plan = fueling::plan(&runner, &race, &running, &Gel::new(40.0, 400.0), Some(120.0));
+
+            assert_eq!(plan.intakes.len(), 11); // the twelfth gel would be at the finish
+            assert_eq!(plan.intakes[0].split, 2);
+            assert_eq!(plan.warnings, vec![Warning::AboveCarbohydrateTolerance, Warning::AboveWeightTolerance, Warning::AboveSodiumTolerance]);
+        }
+
+        #[test]
+        fn test_intakes_before_finish() {
+            let runner: MetricRunner = Runner::new(60.0, 1.70, 30);
+            let race: MetricRace = Race::new(21100);
+            let running: MetricRunning = Running::new(Duration::new(7200, 0));
+            let plan = fueling::plan(&runner, &race, &running, &Gel::new(20.0, 50.0), Some(60.0));
+
+            assert_eq!(plan.intakes.len(), 5);
+            assert!(plan.intakes.iter().all(|intake| intake.elapsed < running.duration()));
+            assert!(plan.warnings.is_empty());
+        }
+
+        #[test]
+        fn test_loading_plan_for_half_marathon() {
+            let runner: ImperialRunner = Runner::new(154.3, 68.0, 30);
+            let running: ImperialRunning = Running::new(Duration::new(6300, 0)); // 1:45:00
+            let plan = fueling::loading_plan(&runner, &running);
+
+            assert_eq!(plan.len(), 1);
+            assert_eq!(plan[0].days_before, 1);
+            assert!((plan[0].per_kg - 10.333).abs() < 0.001);
+            assert_eq!(plan[0].carbohydrate.round(), 723.0);
+        }
+    }
+}
+
+/// API to estimate the running power, as measured by power meters.
+pub mod power {
+    use std::time::Duration;
+    use crate::running::Race;
+    use crate::running::Runner;
+    use crate::running::Running;
+
+    /// Energy cost of running on flat ground, in joules per kilogram per meter (van Dijk and van Megen, 2017).
+    pub const RUNNING_COST: f32 = 0.98;
+
+    /// Acceleration of gravity, in m/s².
+    pub const GRAVITY: f32 = 9.81;
+
+    /// Density of the air at sea level and 15 °C, in kg/m³.
+    pub const AIR_DENSITY: f32 = 1.225;
+
+    /// Drag coefficient times the frontal area of a typical runner, in m².
+    pub const DRAG_AREA: f32 = 0.24;
+
+    /// The power of a runner.
+    pub struct Power {
+        pub watts: f32,
+        pub watts_per_kg: f32,
+    }
+
+    impl Power {
+        /// Creates a new power from the watts and the weight of the runner in kilograms.
+        pub fn new(watts: f32, weight: f32) -> Self {
+            Power {
+                watts,
+                watts_per_kg: watts / weight,
+            }
+        }
+    }
+
+    /// Calculates the power, in watts, of a runner of the weight (kg) running at the speed (m/s) on a grade,
+    /// such as 0.02 for 2%, against a headwind (m/s). Negative headwinds are tailwinds. The power adds up the
+    /// cost of running, climbing and pushing the air, and it is never negative.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::power;
+    ///
+    /// assert_eq!(power::watts(70.0, 4.0, 0.0, 0.0).round(), 284.0);
+    /// assert!(power::watts(70.0, 4.0, 0.02, 0.0) > power::watts(70.0, 4.0, 0.0, 0.0));
+    /// assert!(power::watts(70.0, 4.0, 0.0, 5.0) > power::watts(70.0, 4.0, 0.0, 0.0));
+    /// ```
+    pub fn watts(weight: f32, speed: f32, grade: f32, headwind: f32) -> f32 {
+        let running = RUNNING_COST * weight * speed;
+        let climbing = weight * GRAVITY * grade * speed;
+        let relative = speed + headwind;
+        let air = 0.5 * AIR_DENSITY * DRAG_AREA * relative * relative.abs() * speed;
+
+        (running + climbing + air).max(0.0)
+    }
+
+    /// Estimates the power of the runner to complete the race in the running, on a course with an average
+    /// grade and headwind.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::{Race, Running, Runner, MetricRace, MetricRunning, MetricRunner};
+    /// use librunner::power;
+    /// use librunner::duration;
+    ///
+    /// let runner: MetricRunner = Runner::new(70.0, 1.75, 35);
+    /// let race: MetricRace = Race::new(10000);
+    /// let running: MetricRunning = Running::new(duration::to_duration(0, 50, 0));
+    /// let power = power::estimate(&runner, &race, &running, 0.0, 0.0);
+    ///
+    /// assert_eq!(power.watts.round(), 234.0);
+    /// assert_eq!((power.watts_per_kg * 100.0).round(), 334.0);
+    /// ```
+    pub fn estimate<R: Race>(runner: &impl Runner, race: &R, running: &impl Running, grade: f32, headwind: f32) -> Power {
+        let weight = runner.weight_in_kg();
+        let speed = running.speed(race) * R::UNIT_IN_METERS;
+
+        Power::new(watts(weight, speed, grade, headwind), weight)
+    }
+
+    /// Calculates the speed, in m/s, at which a runner of the weight (kg) produces the power (W) on a grade
+    /// against a headwind (m/s).
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::power;
+    ///
+    /// let watts = power::watts(70.0, 4.0, 0.0, 0.0);
+    /// assert_eq!((power::speed(70.0, watts, 0.0, 0.0) * 100.0).round(), 400.0);
+    /// ```
+    pub fn speed(weight: f32, watts: f32, grade: f32, headwind: f32) -> f32 {
+        let (mut low, mut high) = (0.0_f32, 15.0_f32);
+        for _ in 0..50 {
+            let middle = (low + high) / 2.0;
+            if self::watts(weight, middle, grade, headwind) < watts {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+
+        (low + high) / 2.0
+    }
+
+    /// Calculates the pace, per split distance of the race scale, at which the runner produces the power (W)
+    /// on a segment of the course with a grade and a headwind (m/s), such as the one given by `wind::Wind::headwind`.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::{Runner, MetricRace, ImperialRace, MetricRunner};
+    /// use librunner::wind::Wind;
+    /// use librunner::power;
+    ///
+    /// let runner: MetricRunner = Runner::new(70.0, 1.75, 35);
+    /// let flat = power::pace::<MetricRace>(&runner, 234.1, 0.0, 0.0);
+    /// assert_eq!(flat.as_secs_f32().round(), 300.0); // 5:00/km
+    ///
+    /// let wind = Wind::new(4.0, 0.0);
+    /// let uphill = power::pace::<MetricRace>(&runner, 234.1, 0.03, wind.headwind(0.0));
+    /// assert!(uphill > flat);
+    ///
+    /// let per_mile = power::pace::<ImperialRace>(&runner, 234.1, 0.0, 0.0);
+    /// assert_eq!(per_mile.as_secs_f32().round(), 483.0); // 8:03/mi
+    /// ```
+    pub fn pace<R: Race>(runner: &impl Runner, watts: f32, grade: f32, headwind: f32) -> Duration {
+        let speed = speed(runner.weight_in_kg(), watts, grade, headwind);
+        let meters = R::SPLIT_DISTANCE as f32 * R::UNIT_IN_METERS;
+
+        Duration::from_secs_f32(meters / speed)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::running::{Race, Running, Runner, ImperialRace, ImperialRunning, ImperialRunner, MetricRace};
+        use crate::duration;
+        use crate::power;
+
+        #[test]
+        fn test_imperial_runner_power() {
+            let runner: ImperialRunner = Runner::new(154.3, 68.0, 30);
+            let race: ImperialRace = Race::new(10936); // 10 km in yards
+            let running: ImperialRunning = Running::new(duration::to_duration(0, 50, 0));
+            let power = power::estimate(&runner, &race, &running, 0.0, 0.0);
+
+            assert_eq!(power.watts.round(), 234.0);
+        }
+
+        #[test]
+        fn test_tailwind_and_downhill_reduce_power() {
+            let flat = power::watts(60.0, 3.5, 0.0, 0.0);
+
+            assert!(power::watts(60.0, 3.5, 0.0, -3.0) < flat);
+            assert!(power::watts(60.0, 3.5, -0.05, 0.0) < flat);
+            assert_eq!(power::watts(60.0, 3.5, -0.5, 0.0), 0.0);
+        }
+
+        #[test]
+        fn test_pace_is_inverse_of_power() {
+            let runner: ImperialRunner = Runner::new(154.3, 68.0, 30);
+            let watts = power::watts(runner.weight_in_kg(), 3.0, 0.04, 2.0);
+            let pace = power::pace::<MetricRace>(&runner, watts, 0.04, 2.0);
+
+            assert_eq!(pace.as_secs_f32().round(), 333.0);
+        }
+    }
+}
+
+/// API to calculate cadence and stride length metrics.
+pub mod cadence {
+    use std::time::Duration;
+    use crate::distance;
+    use crate::running::Race;
+
+    /// Cadence, in steps per minute, of a runner as tall as the reference height at the reference speed.
+    pub const REFERENCE_CADENCE: f32 = 170.0;
+
+    /// Height, in meters, of the runner with the reference cadence.
+    pub const REFERENCE_HEIGHT: f32 = 1.75;
+
+    /// Speed, in m/s, of the runner with the reference cadence.
+    pub const REFERENCE_SPEED: f32 = 3.0;
+
+    /// Steps per minute added for each m/s above the reference speed.
+    pub const CADENCE_PER_SPEED: f32 = 7.5;
+
+    /// Calculates the stride length, in meters, of one step at the speed (m/s) and cadence (steps per minute).
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::cadence;
+    ///
+    /// assert_eq!(cadence::stride_length(3.0, 180.0), 1.0);
+    /// ```
+    pub fn stride_length(speed: f32, cadence: f32) -> f32 {
+        speed * 60.0 / cadence
+    }
+
+    /// Estimates the typical cadence, in steps per minute, of a runner of the height (m) at the speed (m/s).
+    /// Cadence increases slowly with the speed, while taller runners take longer and fewer steps, like
+    /// pendulums swinging slower as they get longer.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::cadence;
+    ///
+    /// assert_eq!(cadence::typical_cadence(1.75, 3.0), 170.0);
+    /// assert_eq!(cadence::typical_cadence(1.75, 4.0), 177.5);
+    /// assert!(cadence::typical_cadence(1.90, 3.0) < 170.0);
+    /// ```
+    pub fn typical_cadence(height: f32, speed: f32) -> f32 {
+        (REFERENCE_CADENCE + CADENCE_PER_SPEED * (speed - REFERENCE_SPEED)) * (REFERENCE_HEIGHT / height).sqrt()
+    }
+
+    /// Stride metrics of a split.
+    pub struct Stride {
+        pub cadence: f32,       // steps per minute
+        pub length: f32,        // meters
+        pub length_in_feet: f32,
+        pub steps: u64,
+    }
+
+    /// Calculates the stride metrics of each split of the race, from the time and the cadence recorded in each split.
+    /// Returns `None` when the number of split times or cadences differs from the number of splits of the race.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use librunner::running::{Race, MetricRace};
+    /// use librunner::cadence;
+    ///
+    /// let race: MetricRace = Race::new(2500);
+    /// let splits = [Duration::new(300, 0), Duration::new(290, 0), Duration::new(150, 0)];
+    /// let strides = cadence::strides(&race, &splits, &[180.0, 180.0, 186.0]).unwrap();
+    ///
+    /// assert_eq!((strides[0].length * 1000.0).round(), 1111.0);
+    /// assert_eq!((strides[0].length_in_feet * 100.0).round(), 365.0);
+    /// assert_eq!((strides[1].length * 1000.0).round(), 1149.0);
+    /// assert_eq!(strides[0].steps, 900);
+    /// assert_eq!(strides[2].steps, 465);
+    ///
+    /// assert!(cadence::strides(&race, &splits, &[180.0]).is_none());
+    /// ```
+    pub fn strides<R: Race>(race: &R, splits: &[Duration], cadences: &[f32]) -> Option<Vec<Stride>> {
+        let distances = race.split_distances();
+        if distances.len() != splits.len() || distances.len() != cadences.len() {
+            return None;
+        }
+
+        let mut strides = Vec::new();
+        for ((split, distance), cadence) in splits.iter().zip(distances).zip(cadences) {
+            let speed = distance as f32 * R::UNIT_IN_METERS / split.as_secs_f32();
+            let minutes = split.as_secs_f32() / 60.0;
+            let length = stride_length(speed, *cadence);
+
+            strides.push(Stride {
+                cadence: *cadence,
+                length,
+                length_in_feet: distance::meter_to_feet(length),
+                steps: (cadence * minutes).round() as u64,
+            });
+        }
+
+        Some(strides)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::time::Duration;
+        use crate::running::{Race, ImperialRace};
+        use crate::cadence;
+
+        #[test]
+        fn test_imperial_strides() {
+            let race: ImperialRace = Race::new(3520); // 2 miles
+            let splits = [Duration::new(480, 0), Duration::new(470, 0)];
+            let strides = cadence::strides(&race, &splits, &[170.0, 176.0]).unwrap();
+
+            assert_eq!(strides.len(), 2);
+            assert_eq!((strides[0].length * 100.0).round(), 118.0);
+            assert!(strides[1].length < strides[0].length);
+            assert_eq!(strides[1].steps, 1379);
+        }
+
+        #[test]
+        fn test_same_cadence_at_different_paces() {
+            let race: ImperialRace = Race::new(3520);
+            let splits = [Duration::new(480, 0), Duration::new(420, 0)];
+            let strides = cadence::strides(&race, &splits, &[176.0, 176.0]).unwrap();
+
+            assert!(strides[1].length > strides[0].length);
+        }
+
+        #[test]
+        fn test_strides_without_all_data() {
+            let race: ImperialRace = Race::new(3520);
+            let splits = [Duration::new(480, 0), Duration::new(470, 0)];
+
+            assert!(cadence::strides(&race, &splits, &[170.0]).is_none());
+            assert!(cadence::strides(&race, &splits, &[170.0, 172.0, 174.0]).is_none());
+            assert!(cadence::strides(&race, &splits[..1], &[170.0, 172.0]).is_none());
+        }
+    }
+}
+
+/// API to estimate the lactate threshold from field tests.
+pub mod threshold {
+    use std::time::Duration;
+    use crate::distance;
+    use crate::running::Race;
+    use crate::running::Running;
+
+    /// Exponent of the Riegel formula to project a race time to another distance.
+    pub const RIEGEL_EXPONENT: f32 = 1.06;
+
+    /// Duration, in seconds, of the effort that can be sustained at the lactate threshold.
+    pub const THRESHOLD_DURATION: f32 = 3600.0;
+
+    /// Blood lactate, in mmol/L, of the fixed 4 mmol method.
+    pub const FIXED_LACTATE: f32 = 4.0;
+
+    /// The lactate threshold of a runner.
+    #[derive(Debug, PartialEq)]
+    pub struct Threshold {
+        pub pace_per_km: Duration,
+        pub pace_per_mile: Duration,
+        pub heart_rate: Option<u64>, // beats per minute, when it can be estimated
+    }
+
+    impl Threshold {
+        /// Creates a new threshold from the speed in m/s, with the paces rounded to the second.
+        ///
+        /// Example:
+        ///
+        /// ```
+        /// use std::time::Duration;
+        /// use librunner::threshold::Threshold;
+        ///
+        /// let threshold = Threshold::new(4.0, Some(170));
+        /// assert_eq!(threshold.pace_per_km, Duration::new(250, 0));
+        /// assert_eq!(threshold.pace_per_mile, Duration::new(402, 0));
+        /// ```
+        pub fn new(speed: f32, heart_rate: Option<u64>) -> Self {
+            Threshold {
+                pace_per_km: Duration::new((distance::METERS_IN_KM / speed).round() as u64, 0),
+                pace_per_mile: Duration::new((distance::METERS_IN_MILE / speed).round() as u64, 0),
+                heart_rate,
+            }
+        }
+    }
+
+    /// Estimates the threshold from a 30-minute time trial run alone, where the race is the distance covered.
+    /// The threshold pace is the average pace of the trial and the threshold heart rate is the average heart
+    /// rate of its last 20 minutes, as proposed by Joe Friel.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use librunner::running::{Race, Running, MetricRace, MetricRunning};
+    /// use librunner::threshold;
+    /// use librunner::duration;
+    ///
+    /// let covered: MetricRace = Race::new(7500);
+    /// let running: MetricRunning = Running::new(duration::to_duration(0, 30, 0));
+    /// let threshold = threshold::time_trial(&covered, &running, Some(168));
+    ///
+    /// assert_eq!(threshold.pace_per_km, Duration::new(240, 0)); // 4:00/km
+    /// assert_eq!(threshold.heart_rate, Some(168));
+    /// ```
+    pub fn time_trial<R: Race>(covered: &R, running: &impl Running, heart_rate: Option<u64>) -> Threshold {
+        Threshold::new(running.speed(covered) * R::UNIT_IN_METERS, heart_rate)
+    }
+
+    /// Estimates the threshold pace from a race between 3K and 10K, projecting it with the Riegel formula to the
+    /// distance that would be covered in one hour. The heart rate of a race varies too much with the distance to
+    /// estimate the threshold heart rate, so it is unknown.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use librunner::running::{Race, Running, MetricRace, MetricRunning};
+    /// use librunner::threshold;
+    /// use librunner::duration;
+    ///
+    /// let race: MetricRace = Race::new(5000);
+    /// let running: MetricRunning = Running::new(duration::to_duration(0, 20, 0));
+    /// let threshold = threshold::race(&race, &running);
+    ///
+    /// assert_eq!(threshold.pace_per_km, Duration::new(255, 0)); // 4:15/km
+    /// assert_eq!(threshold.heart_rate, None);
+    /// ```
+    pub fn race<R: Race>(race: &R, running: &impl Running) -> Threshold {
+        let meters = race.distance() as f32 * R::UNIT_IN_METERS;
+        let seconds = running.duration().as_secs_f32();
+        let projected = meters * (THRESHOLD_DURATION / seconds).powf(1.0 / RIEGEL_EXPONENT);
+
+        Threshold::new(projected / THRESHOLD_DURATION, None)
+    }
+
+    /// A stage of an incremental step test.
+    pub struct Stage {
+        pub pace: Duration,  // per km
+        pub heart_rate: u64, // beats per minute at the end of the stage
+        pub lactate: f32,    // mmol/L at the end of the stage
+    }
+
+    impl Stage {
+        pub fn new(pace: Duration, heart_rate: u64, lactate: f32) -> Self {
+            Stage {
+                pace,
+                heart_rate,
+                lactate,
+            }
+        }
+
+        fn speed(&self) -> f32 {
+            distance::METERS_IN_KM / self.pace.as_secs_f32()
+        }
+    }
+
+    /// Methods to find the threshold in the lactate curve of a step test.
+    pub enum Method {
+        FixedLactate, // where the lactate reaches 4 mmol/L
+        Dmax,         // farthest point of the fitted curve from the line between the first and last stages
+    }
+
+    /// Estimates the threshold from the stages of a step test, ordered from the slowest to the fastest.
+    /// The heart rate is interpolated between the stages around the threshold speed. Returns `None` when the
+    /// lactate never reaches 4 mmol/L with the fixed lactate method or there are fewer than four stages to fit
+    /// the cubic curve of the Dmax method.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use librunner::threshold::{self, Method, Stage};
+    ///
+    /// let stages = [
+    ///     Stage::new(Duration::new(360, 0), 140, 1.0),
+    ///     Stage::new(Duration::new(330, 0), 150, 1.1),
+    ///     Stage::new(Duration::new(300, 0), 160, 1.4),
+    ///     Stage::new(Duration::new(270, 0), 170, 2.2),
+    ///     Stage::new(Duration::new(255, 0), 176, 3.4),
+    ///     Stage::new(Duration::new(240, 0), 182, 5.6),
+    /// ];
+    ///
+    /// let fixed = threshold::step_test(&stages, &Method::FixedLactate).unwrap();
+    /// assert_eq!(fixed.pace_per_km, Duration::new(251, 0));
+    /// assert_eq!(fixed.heart_rate, Some(178));
+    ///
+    /// let dmax = threshold::step_test(&stages, &Method::Dmax).unwrap();
+    /// assert!(dmax.pace_per_km > fixed.pace_per_km);
+    /// ```
+    pub fn step_test(stages: &[Stage], method: &Method) -> Option<Threshold> {
+        let speed = match method {
+            Method::FixedLactate => fixed_lactate_speed(stages)?,
+            Method::Dmax => dmax_speed(stages)?,
+        };
+
+        Some(Threshold::new(speed, Some(heart_rate_at(stages, speed))))
+    }
+
+    fn fixed_lactate_speed(stages: &[Stage]) -> Option<f32> {
+        for pair in stages.windows(2) {
+            let (before, after) = (&pair[0], &pair[1]);
+            if before.lactate < FIXED_LACTATE && after.lactate >= FIXED_LACTATE {
+                let fraction = (FIXED_LACTATE - before.lactate) / (after.lactate - before.lactate);
+                return Some(before.speed() + fraction * (after.speed() - before.speed()));
+            }
+        }
+
+        None
+    }
+
+    fn dmax_speed(stages: &[Stage]) -> Option<f32> {
+        if stages.len() < 4 {
+            return None;
+        }
+
+        let curve = fit_cubic(stages);
+        let (first, last) = (&stages[0], &stages[stages.len() - 1]);
+        let slope = (last.lactate - first.lactate) / (last.speed() - first.speed());
+
+        // the curve is sampled for the speed where it is farthest below the line
+        let mut best = (first.speed(), 0.0);
+        for i in 0..=1000 {
+            let speed = first.speed() + (last.speed() - first.speed()) * i as f32 / 1000.0;
+            let line = first.lactate + slope * (speed - first.speed());
+            let distance = line - curve.iter().rev().fold(0.0, |sum, c| sum * speed + c);
+            if distance > best.1 {
+                best = (speed, distance);
+            }
+        }
+
+        Some(best.0)
+    }
+
+    /// Fits the coefficients, from the constant to the cubic term, of the lactate by speed with least squares.
+    fn fit_cubic(stages: &[Stage]) -> [f32; 4] {
+        // normal equations, solved by Gaussian elimination
+        let mut matrix = [[0.0_f64; 5]; 4];
+        for stage in stages {
+            let speed = stage.speed() as f64;
+            for (row, line) in matrix.iter_mut().enumerate() {
+                for (column, cell) in line.iter_mut().take(4).enumerate() {
+                    *cell += speed.powi((row + column) as i32);
+                }
+                line[4] += speed.powi(row as i32) * stage.lactate as f64;
+            }
+        }
+
+        for pivot in 0..4 {
+            let pivot_line = matrix[pivot];
+            for line in matrix.iter_mut().skip(pivot + 1) {
+                let factor = line[pivot] / pivot_line[pivot];
+                for (cell, value) in line.iter_mut().zip(pivot_line).skip(pivot) {
+                    *cell -= factor * value;
+                }
+            }
+        }
+
+        let mut coefficients = [0.0_f64; 4];
+        for row in (0..4).rev() {
+            let known: f64 = (row + 1..4).map(|column| matrix[row][column] * coefficients[column]).sum();
+            coefficients[row] = (matrix[row][4] - known) / matrix[row][row];
+        }
+
+        coefficients.map(|c| c as f32)
+    }
+
+    fn heart_rate_at(stages: &[Stage], speed: f32) -> u64 {
+        for pair in stages.windows(2) {
+            let (before, after) = (&pair[0], &pair[1]);
+            if speed <= after.speed() {
+                let fraction = ((speed - before.speed()) / (after.speed() - before.speed())).max(0.0);
+                let heart_rate = before.heart_rate as f32 + fraction * (after.heart_rate as f32 - before.heart_rate as f32);
+                return heart_rate.round() as u64;
+            }
+        }
+
+        stages[stages.len() - 1].heart_rate
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::time::Duration;
+        use crate::running::{Race, Running, ImperialRace, ImperialRunning};
+        use crate::threshold::{self, Method, Stage};
+        use crate::duration;
+
+        #[test]
+        fn test_imperial_race() {
+            let race: ImperialRace = Race::new(10936); // 10K in yards
+            let running: ImperialRunning = Running::new(duration::to_duration(0, 45, 0));
+            let threshold = threshold::race(&race, &running);
+
+            assert_eq!(threshold.pace_per_km, Duration::new(274, 0));
+            assert_eq!(threshold.pace_per_mile, Duration::new(442, 0));
+        }
+
+        #[test]
+        fn test_step_test_without_threshold() {
+            let stages = [
+                Stage::new(Duration::new(360, 0), 140, 1.0),
+                Stage::new(Duration::new(330, 0), 150, 1.2),
+                Stage::new(Duration::new(300, 0), 160, 2.5),
+            ];
+
+            assert_eq!(threshold::step_test(&stages, &Method::FixedLactate), None);
+            assert_eq!(threshold::step_test(&stages, &Method::Dmax), None);
+        }
+    }
+}
+
+/// API to estimate the recovery after a race.
+pub mod recovery {
+    use std::time::Duration;
+    use crate::distance;
+    use crate::running::Race;
+    use crate::running::Runner;
+    use crate::running::Running;
+
+    /// Fraction of the effort of the race at which the recovery starts to be needed, relative to the predicted best.
+    pub const EASY_EFFORT: f32 = 0.8;
+
+    /// Age from which the recovery takes longer.
+    pub const AGE_REFERENCE: u64 = 30;
+
+    /// Fraction of the recovery added for each year above the reference age.
+    pub const AGE_FACTOR: f32 = 0.01;
+
+    /// Fraction of the recovery until the runner can run easy again.
+    pub const EASY_FRACTION: f32 = 0.15;
+
+    /// Fraction of the recovery until the runner can do quality sessions again.
+    pub const QUALITY_FRACTION: f32 = 0.4;
+
+    /// Days of recovery after a race.
+    #[derive(Debug, PartialEq)]
+    pub struct Recovery {
+        pub easy_running: u64,
+        pub quality_sessions: u64,
+        pub next_race: u64,
+    }
+
+    /// Estimates the recovery of the runner after completing the race in the running, following the rule of
+    /// one day of recovery for each mile raced. The days are reduced when the race was easier than the predicted
+    /// best time of the runner for the distance, increased with the age of the runner, and adjusted by how long
+    /// the race was compared to the weekly distance of recent training, in the same units of the race.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::{Race, Running, Runner, MetricRace, MetricRunning, MetricRunner};
+    /// use librunner::recovery::{self, Recovery};
+    /// use librunner::duration;
+    ///
+    /// let runner: MetricRunner = Runner::new(70.0, 1.75, 40);
+    /// let race: MetricRace = Race::new(42195);
+    /// let running: MetricRunning = Running::new(duration::to_duration(3, 30, 0));
+    /// let best = duration::to_duration(3, 30, 0);
+    ///
+    /// assert_eq!(recovery::estimate(&runner, &race, &running, best, 60000), Recovery {
+    ///     easy_running: 5,
+    ///     quality_sessions: 13,
+    ///     next_race: 32,
+    /// });
+    /// ```
+    pub fn estimate<R: Race>(runner: &impl Runner, race: &R, running: &impl Running, best: Duration, weekly_distance: u64) -> Recovery {
+        let miles = race.distance() as f32 * R::UNIT_IN_METERS / distance::METERS_IN_MILE;
+
+        let intensity = best.as_secs_f32() / running.duration().as_secs_f32();
+        let effort = ((intensity - EASY_EFFORT) / (1.0 - EASY_EFFORT)).clamp(0.0, 1.0);
+        let age = 1.0 + AGE_FACTOR * runner.age().saturating_sub(AGE_REFERENCE) as f32;
+        let load = (0.75 + 0.5 * race.distance() as f32 / weekly_distance as f32).clamp(0.75, 1.5);
+
+        let days = miles * (0.5 + 0.5 * effort) * age * load;
+
+        Recovery {
+            easy_running: (days * EASY_FRACTION).ceil() as u64,
+            quality_sessions: (days * QUALITY_FRACTION).ceil() as u64,
+            next_race: days.ceil() as u64,
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::running::{Race, Running, Runner, ImperialRace, ImperialRunning, ImperialRunner};
+        use crate::recovery::{self, Recovery};
+        use crate::duration;
+
+        #[test]
+        fn test_easy_5k() {
+            let runner: ImperialRunner = Runner::new(154.3, 68.0, 25);
+            let race: ImperialRace = Race::new(5468); // 5K in yards
+            let running: ImperialRunning = Running::new(duration::to_duration(0, 25, 0));
+            let best = duration::to_duration(0, 20, 0);
+
+            assert_eq!(recovery::estimate(&runner, &race, &running, best, 70400), Recovery {
+                easy_running: 1,
+                quality_sessions: 1,
+                next_race: 2,
+            });
+        }
+
+        #[test]
+        fn test_harder_without_training() {
+            let runner: ImperialRunner = Runner::new(154.3, 68.0, 50);
+            let race: ImperialRace = Race::new(23056); // half marathon in yards
+            let running: ImperialRunning = Running::new(duration::to_duration(1, 40, 0));
+            let best = duration::to_duration(1, 40, 0);
+            let trained = recovery::estimate(&runner, &race, &running, best, 70400);
+            let untrained = recovery::estimate(&runner, &race, &running, best, 0);
+
+            assert!(untrained.next_race > trained.next_race);
+            assert_eq!(untrained.next_race, 24);
+        }
+    }
+}
+
+/// API to calculate the training load of a running session.
+pub mod training_load {
+    use std::time::Duration;
+    use crate::heart_rate::{self, MaxFormula};
+    use crate::running::Runner;
+    use crate::running::Running;
+    use crate::running::Sex;
+
+    /// Factor to convert the Edwards TRIMP of men to Banister TRIMP units. Both give the same load to 60 minutes
+    /// at 70% of the heart rate reserve, which is zone 3 for a resting heart rate of 60 and a maximum of 190.
+    pub const EDWARDS_TO_TRIMP_MALE: f32 = 0.57;
+
+    /// Factor to convert the Edwards TRIMP of women to Banister TRIMP units, calibrated like the one of men
+    /// on the Banister curve of women, which gives a higher load to the same session.
+    pub const EDWARDS_TO_TRIMP_FEMALE: f32 = 0.65;
+
+    /// Factor to convert the session-RPE load of men to Banister TRIMP units. Both give the same load to
+    /// 60 minutes at 70% of the heart rate reserve, perceived as hard (5).
+    pub const SESSION_RPE_TO_TRIMP_MALE: f32 = 0.34;
+
+    /// Factor to convert the session-RPE load of women to Banister TRIMP units, calibrated like the one of men
+    /// on the Banister curve of women.
+    pub const SESSION_RPE_TO_TRIMP_FEMALE: f32 = 0.39;
+
+    /// Methods to calculate the training load.
+    #[derive(Debug, PartialEq)]
+    pub enum Method {
+        Banister,   // from the average heart rate, as a fraction of the heart rate reserve
+        Edwards,    // from the time in each of the five zones of the maximum heart rate
+        SessionRpe, // from the rate of perceived exertion, from 1 to 10
+    }
+
+    /// The training load of a session, in Banister TRIMP units, and the method used to calculate it.
+    #[derive(Debug, PartialEq)]
+    pub struct Load {
+        pub value: f32,
+        pub method: Method,
+    }
+
+    /// Data recorded about a session, which may not have all of it.
+    #[derive(Default)]
+    pub struct Session {
+        pub average_heart_rate: Option<u64>,
+        pub time_in_zones: Option<Vec<Duration>>, // from zone 1 to zone 5 of `heart_rate::percent_max_zones`
+        pub rpe: Option<u8>,
+    }
+
+    /// Calculates the Banister TRIMP of the running, from the average, resting and maximum heart rates of the
+    /// session. The fraction of the heart rate reserve is weighted by the rise of blood lactate, which differs
+    /// between men and women. The fraction is kept between 0.0 and 1.0, and there is no load when the maximum
+    /// heart rate isn't above the resting one.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::{Running, MetricRunning, Sex};
+    /// use librunner::training_load;
+    /// use librunner::duration;
+    ///
+    /// let running: MetricRunning = Running::new(duration::to_duration(1, 0, 0));
+    /// assert_eq!(training_load::banister(&running, 150, 50, 190, Sex::Male).round(), 108.0);
+    /// assert_eq!(training_load::banister(&running, 150, 50, 190, Sex::Female).round(), 121.0);
+    /// assert_eq!(training_load::banister(&running, 150, 190, 190, Sex::Male), 0.0);
+    /// ```
+    pub fn banister(running: &impl Running, average: u64, resting: u64, max: u64, sex: Sex) -> f32 {
+        if max <= resting {
+            return 0.0;
+        }
+
+        let minutes = running.duration().as_secs_f32() / 60.0;
+        let reserve = ((average as f32 - resting as f32) / (max as f32 - resting as f32)).clamp(0.0, 1.0);
+        let weight = match sex {
+            Sex::Male => 0.64 * (1.92 * reserve).exp(),
+            Sex::Female => 0.86 * (1.67 * reserve).exp(),
+        };
+
+        minutes * reserve * weight
+    }
+
+    /// Calculates the Edwards TRIMP from the time spent in each zone, multiplying the minutes by the number
+    /// of the zone.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::training_load;
+    /// use librunner::duration;
+    ///
+    /// let zones = [
+    ///     duration::to_duration(0, 10, 0),
+    ///     duration::to_duration(0, 10, 0),
+    ///     duration::to_duration(0, 20, 0),
+    ///     duration::to_duration(0, 15, 0),
+    ///     duration::to_duration(0, 5, 0),
+    /// ];
+    /// assert_eq!(training_load::edwards(&zones), 175.0);
+    /// ```
+    pub fn edwards(time_in_zones: &[Duration]) -> f32 {
+        time_in_zones.iter()
+            .enumerate()
+            .map(|(i, time)| time.as_secs_f32() / 60.0 * (i + 1) as f32)
+            .sum()
+    }
+
+    /// Calculates the session-RPE load of the running, multiplying its minutes by the rate of perceived
+    /// exertion in the CR-10 scale.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::{Running, MetricRunning};
+    /// use librunner::training_load;
+    /// use librunner::duration;
+    ///
+    /// let running: MetricRunning = Running::new(duration::to_duration(1, 0, 0));
+    /// assert_eq!(training_load::session_rpe(&running, 6), 360.0);
+    /// ```
+    pub fn session_rpe(running: &impl Running, rpe: u8) -> f32 {
+        running.duration().as_secs_f32() / 60.0 * rpe as f32
+    }
+
+    /// Calculates the training load of the running, in Banister TRIMP units, with the most precise method the data
+    /// of the session allows: Banister when the average heart rate and the resting heart rate and sex of the runner
+    /// are known, then Edwards when the time in zones is known, and finally session-RPE. Edwards and session-RPE
+    /// loads are converted to Banister TRIMP units of the sex of the runner, so loads of different sessions can be
+    /// compared, using the average of the factors of men and women when the sex is unknown. The maximum heart rate
+    /// is estimated with the formula when it is unknown. Returns `None` when the session has no data.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use librunner::running::{Running, Runner, MetricRunning, MetricRunner, Sex};
+    /// use librunner::heart_rate::MaxFormula;
+    /// use librunner::training_load::{self, Method, Session};
+    /// use librunner::duration;
+    ///
+    /// let mut runner: MetricRunner = Runner::new(70.0, 1.75, 35);
+    /// let running: MetricRunning = Running::new(duration::to_duration(1, 0, 0));
+    /// let session = Session {
+    ///     average_heart_rate: Some(150),
+    ///     rpe: Some(6),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let load = training_load::load(&runner, &running, &session, &MaxFormula::Tanaka).unwrap();
+    /// assert_eq!(load.method, Method::SessionRpe);
+    /// assert_eq!(load.value.round(), 131.0);
+    ///
+    /// runner.heart_rate.resting = Some(50);
+    /// runner.heart_rate.max = Some(190);
+    /// runner.sex = Some(Sex::Male);
+    /// let load = training_load::load(&runner, &running, &session, &MaxFormula::Tanaka).unwrap();
+    /// assert_eq!(load.method, Method::Banister);
+    /// assert_eq!(load.value.round(), 108.0);
+    ///
+    /// assert_eq!(training_load::load(&runner, &running, &Session::default(), &MaxFormula::Tanaka), None);
+    /// ```
+    pub fn load(runner: &impl Runner, running: &impl Running, session: &Session, formula: &MaxFormula) -> Option<Load> {
+        let max = heart_rate::max(runner, formula);
+        if let (Some(average), Some(resting), Some(sex)) = (session.average_heart_rate, runner.heart_rate().resting, runner.sex()) {
+            if max > resting {
+                return Some(Load {
+                    value: banister(running, average, resting, max, sex),
+                    method: Method::Banister,
+                });
+            }
+        }
+
+        let (edwards_to_trimp, session_rpe_to_trimp) = match runner.sex() {
+            Some(Sex::Male) => (EDWARDS_TO_TRIMP_MALE, SESSION_RPE_TO_TRIMP_MALE),
+            Some(Sex::Female) => (EDWARDS_TO_TRIMP_FEMALE, SESSION_RPE_TO_TRIMP_FEMALE),
+            None => (
+                (EDWARDS_TO_TRIMP_MALE + EDWARDS_TO_TRIMP_FEMALE) / 2.0,
+                (SESSION_RPE_TO_TRIMP_MALE + SESSION_RPE_TO_TRIMP_FEMALE) / 2.0,
+            ),
+        };
+
+        if let Some(time_in_zones) = &session.time_in_zones {
+            return Some(Load {
+                value: edwards(time_in_zones) * edwards_to_trimp,
+                method: Method::Edwards,
+            });
+        }
+
+        session.rpe.map(|rpe| Load {
+            value: session_rpe(running, rpe) * session_rpe_to_trimp,
+            method: Method::SessionRpe,
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::time::Duration;
+        use crate::running::{Running, Runner, ImperialRunning, ImperialRunner, Sex};
+        use crate::heart_rate::MaxFormula;
+        use crate::training_load::{self, Method, Session};
+
+        #[test]
+        fn test_edwards_without_heart_rate_data() {
+            let mut runner: ImperialRunner = Runner::new(132.0, 64.0, 28);
+            runner.sex = Some(Sex::Female);
+            let running: ImperialRunning = Running::new(Duration::new(2700, 0));
+            let session = Session {
+                time_in_zones: Some(vec![Duration::new(600, 0), Duration::new(1200, 0), Duration::new(900, 0)]),
+                rpe: Some(4),
+                ..Default::default()
+            };
+            let load = training_load::load(&runner, &running, &session, &MaxFormula::Gulati).unwrap();
+
+            assert_eq!(load.method, Method::Edwards);
+            assert_eq!(load.value.round(), 62.0);
+        }
+
+        #[test]
+        fn test_banister_reserve_limits() {
+            let running: ImperialRunning = Running::new(Duration::new(3600, 0));
+
+            assert_eq!(training_load::banister(&running, 200, 60, 190, Sex::Male), training_load::banister(&running, 190, 60, 190, Sex::Male));
+            assert_eq!(training_load::banister(&running, 50, 60, 190, Sex::Female), 0.0);
+            assert_eq!(training_load::banister(&running, 150, 190, 180, Sex::Female), 0.0);
+        }
+
+        #[test]
+        fn test_rpe_when_resting_heart_rate_is_not_below_max() {
+            let mut runner: ImperialRunner = Runner::new(132.0, 64.0, 28);
+            runner.sex = Some(Sex::Female);
+            runner.heart_rate.resting = Some(185);
+            runner.heart_rate.max = Some(185);
+            let running: ImperialRunning = Running::new(Duration::new(3600, 0));
+            let session = Session {
+                average_heart_rate: Some(160),
+                rpe: Some(5),
+                ..Default::default()
+            };
+            let load = training_load::load(&runner, &running, &session, &MaxFormula::Gulati).unwrap();
+
+            assert_eq!(load.method, Method::SessionRpe);
+            assert_eq!(load.value.round(), 117.0);
+        }
+
+        #[test]
+        fn test_banister_with_estimated_max() {
+            let mut runner: ImperialRunner = Runner::new(132.0, 64.0, 28);
+            runner.sex = Some(Sex::Female);
+            runner.heart_rate.resting = Some(55);
+            let running: ImperialRunning = Running::new(Duration::new(2700, 0));
+            let session = Session {
+                average_heart_rate: Some(160),
+                ..Default::default()
+            };
+            let load = training_load::load(&runner, &running, &session, &MaxFormula::Gulati).unwrap();
+
+            assert_eq!(load.method, Method::Banister);
+            assert!(load.value > training_load::banister(&running, 160, 55, 200, Sex::Female));
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::Duration;